@@ -1,13 +1,32 @@
 use mtml_parser::{
     ast::{Node, Node::*, RootNode},
-    parse as parse_mtml, serialize as serialize_mtml,
+    parse as parse_mtml, serialize as serialize_mtml, ParseError,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
+#[derive(Debug, Serialize)]
+struct ParseErrorObject {
+    message: String,
+    error: ParseError,
+}
+
+impl From<ParseError> for ParseErrorObject {
+    fn from(error: ParseError) -> Self {
+        return ParseErrorObject {
+            message: error.to_string(),
+            error,
+        };
+    }
+}
+
 #[wasm_bindgen]
 pub fn parse(input: &str) -> Result<JsValue, JsValue> {
-    return match parse_mtml(input)? {
+    let node = match parse_mtml(input) {
+        Ok(node) => node,
+        Err(err) => return Err(serde_wasm_bindgen::to_value(&ParseErrorObject::from(err))?),
+    };
+    return match node {
         Root(node) => Ok(serde_wasm_bindgen::to_value(&node)?),
         node => Ok(serde_wasm_bindgen::to_value(&node)?),
     };
@@ -20,15 +39,53 @@ pub enum FunctionTagStyle {
     SelfClosing = 2,
 }
 
+#[wasm_bindgen]
+#[derive(Debug, Deserialize)]
+pub enum NameCase {
+    Preserve = 1,
+    Canonical = 2,
+    Lower = 3,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Deserialize)]
+pub enum AttributeOrder {
+    Preserve = 1,
+    ArgumentsFirst = 2,
+}
+
+fn to_name_case(value: u8) -> mtml_parser::serializer::NameCase {
+    return match value {
+        2 => mtml_parser::serializer::NameCase::Canonical,
+        3 => mtml_parser::serializer::NameCase::Lower,
+        _ => mtml_parser::serializer::NameCase::Preserve,
+    };
+}
+
 #[derive(Debug, Deserialize)]
 #[allow(non_snake_case)]
 struct SerializeOptions {
     prefix: String,
     functionTagStyle: u8,
+    #[serde(default)]
+    lossless: bool,
+    #[serde(default)]
+    pretty: bool,
+    indentWidth: Option<usize>,
+    #[serde(default)]
+    indentTabs: bool,
+    maxWidth: Option<usize>,
+    #[serde(default)]
+    tagCase: u8,
+    #[serde(default)]
+    attributeCase: u8,
+    #[serde(default)]
+    attributeOrder: u8,
 }
 
 impl SerializeOptions {
     fn to_options(&self) -> mtml_parser::serializer::Options {
+        let defaults = mtml_parser::serializer::Options::default();
         return mtml_parser::serializer::Options {
             prefix: self.prefix.clone(),
             function_tag_style: match self.functionTagStyle {
@@ -39,6 +96,17 @@ impl SerializeOptions {
                     mtml_parser::serializer::FunctionTagStyle::Dollar
                 },
             },
+            lossless: self.lossless,
+            pretty: self.pretty,
+            indent_width: self.indentWidth.unwrap_or(defaults.indent_width),
+            indent_tabs: self.indentTabs,
+            max_width: self.maxWidth.unwrap_or(defaults.max_width),
+            tag_case: to_name_case(self.tagCase),
+            attribute_case: to_name_case(self.attributeCase),
+            attribute_order: match self.attributeOrder {
+                2 => mtml_parser::serializer::AttributeOrder::ArgumentsFirst,
+                _ => mtml_parser::serializer::AttributeOrder::Preserve,
+            },
         };
     }
 }
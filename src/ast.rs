@@ -1,21 +1,84 @@
 extern crate serde;
 use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub line: u32,
+    pub column: usize,
+    pub offset: usize,
+}
+
+pub(crate) fn is_false(value: &bool) -> bool {
+    return !*value;
+}
+
+/// Source range from `start` (inclusive) to `end` (exclusive).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceRange {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// Quote character an attribute value was written with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuoteStyle {
+    Double,
+    Single,
+    None,
+}
+
+/// How a tag was delimited: `<$mt:Tag$>`, `<mt:Tag/>` or `<mt:Tag>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TagStyle {
+    Dollar,
+    SelfClosing,
+    Plain,
+}
+
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AttributeValue {
     pub value: String,
+    pub quote: QuoteStyle,
+    /// Nodes parsed from a value containing MT tags, such as
+    /// `value="<$mt:EntryTitle$>"`, when `ParserConfig::attribute_tags` is enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<Node>>,
     pub line: u32,
     pub column: usize,
     pub offset: usize,
+    pub end_line: u32,
+    pub end_column: usize,
+    pub end_offset: usize,
+}
+
+/// How an attribute was written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AttributeSyntax {
+    /// Every value is quoted, e.g. `lastn="5"` or `replace="a",'b'`.
+    Quoted,
+    /// At least one value is unquoted, e.g. `lastn=5`.
+    Unquoted,
+    /// The attribute has no `=` and no values, e.g. `<mt:Var foo>`, which
+    /// Movable Type reads as `name="foo"`.
+    Valueless,
 }
 
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Attribute {
     pub name: String,
     pub values: Vec<AttributeValue>,
+    /// Whitespace between the previous token and the attribute name, as written.
+    pub leading_whitespace: String,
+    pub syntax: AttributeSyntax,
     pub line: u32,
     pub column: usize,
     pub offset: usize,
+    pub end_line: u32,
+    pub end_column: usize,
+    pub end_offset: usize,
+    /// Set by the editing API, see `edit`.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub synthetic: bool,
 }
 
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -29,15 +92,32 @@ pub struct TextNode {
     pub line: u32,
     pub column: usize,
     pub offset: usize,
+    pub end_line: u32,
+    pub end_column: usize,
+    pub end_offset: usize,
+    /// Set by the editing API, see `edit`.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub synthetic: bool,
 }
 
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FunctionTagNode {
     pub name: String,
     pub attributes: Vec<Attribute>,
+    /// Prefix as written, e.g. `mt:`, `MT` or `mt`.
+    pub prefix: String,
+    pub style: TagStyle,
+    /// Source between the last attribute and the closing `>`, e.g. ` $` or ` /`.
+    pub tail: String,
     pub line: u32,
     pub column: usize,
     pub offset: usize,
+    pub end_line: u32,
+    pub end_column: usize,
+    pub end_offset: usize,
+    /// Set by the editing API, see `edit`.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub synthetic: bool,
 }
 
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -45,16 +125,209 @@ pub struct BlockTagNode {
     pub name: String,
     pub attributes: Vec<Attribute>,
     pub children: Vec<Node>,
+    /// Prefix as written, e.g. `mt:`, `MT` or `mt`.
+    pub prefix: String,
+    pub style: TagStyle,
+    /// Source between the last attribute and the closing `>`.
+    pub tail: String,
+    /// Prefix of the closing tag as written, empty when the closing tag is missing.
+    pub closing_prefix: String,
+    /// Name in the closing tag as written, empty when the closing tag is missing.
+    pub closing_name: String,
+    pub line: u32,
+    pub column: usize,
+    pub offset: usize,
+    pub end_line: u32,
+    pub end_column: usize,
+    pub end_offset: usize,
+    pub open_tag: SourceRange,
+    pub body: SourceRange,
+    /// `None` when the closing tag is missing in a recovered AST.
+    pub close_tag: Option<SourceRange>,
+    /// Set for conditional tags such as `<mt:If>`, which may contain `<mt:Else>`.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub conditional: bool,
+    /// Set by the editing API, see `edit`.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub synthetic: bool,
+}
+
+/// `<mt:Ignore>` block, whose body is kept as raw text instead of being parsed.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IgnoreNode {
+    pub name: String,
+    pub attributes: Vec<Attribute>,
+    /// Body as written.
+    pub value: String,
+    pub prefix: String,
+    pub style: TagStyle,
+    pub tail: String,
+    pub closing_prefix: String,
+    pub closing_name: String,
+    pub line: u32,
+    pub column: usize,
+    pub offset: usize,
+    pub end_line: u32,
+    pub end_column: usize,
+    pub end_offset: usize,
+    pub open_tag: SourceRange,
+    pub body: SourceRange,
+    pub close_tag: Option<SourceRange>,
+}
+
+/// HTML comment, parsed only when `ParserConfig::html_comments` is enabled.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommentNode {
+    /// Text between `<!--` and `-->` as written, including any MT tags.
+    pub value: String,
+    pub line: u32,
+    pub column: usize,
+    pub offset: usize,
+    pub end_line: u32,
+    pub end_column: usize,
+    pub end_offset: usize,
+}
+
+/// Source text that could not be parsed, kept verbatim by `parse_with_recovery`.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorNode {
+    pub value: String,
     pub line: u32,
     pub column: usize,
     pub offset: usize,
+    pub end_line: u32,
+    pub end_column: usize,
+    pub end_offset: usize,
+}
+
+/// Placeholder for the closing tag of `name`, which was expected at this position.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MissingNode {
+    pub name: String,
+    pub line: u32,
+    pub column: usize,
+    pub offset: usize,
+    pub end_line: u32,
+    pub end_column: usize,
+    pub end_offset: usize,
 }
 
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "type")]
+#[allow(clippy::large_enum_variant)]
 pub enum Node {
     Root(RootNode),
     Text(TextNode),
     FunctionTag(FunctionTagNode),
     BlockTag(BlockTagNode),
+    Ignore(IgnoreNode),
+    Comment(CommentNode),
+    Error(ErrorNode),
+    Missing(MissingNode),
+}
+
+impl Node {
+    /// Return the source range of the node, or `None` for `Root`.
+    pub fn range(&self) -> Option<SourceRange> {
+        let (line, column, offset, end_line, end_column, end_offset) = match self {
+            Node::Root(_) => return None,
+            Node::Text(n) => (
+                n.line,
+                n.column,
+                n.offset,
+                n.end_line,
+                n.end_column,
+                n.end_offset,
+            ),
+            Node::FunctionTag(n) => (
+                n.line,
+                n.column,
+                n.offset,
+                n.end_line,
+                n.end_column,
+                n.end_offset,
+            ),
+            Node::BlockTag(n) => (
+                n.line,
+                n.column,
+                n.offset,
+                n.end_line,
+                n.end_column,
+                n.end_offset,
+            ),
+            Node::Ignore(n) => (
+                n.line,
+                n.column,
+                n.offset,
+                n.end_line,
+                n.end_column,
+                n.end_offset,
+            ),
+            Node::Comment(n) => (
+                n.line,
+                n.column,
+                n.offset,
+                n.end_line,
+                n.end_column,
+                n.end_offset,
+            ),
+            Node::Error(n) => (
+                n.line,
+                n.column,
+                n.offset,
+                n.end_line,
+                n.end_column,
+                n.end_offset,
+            ),
+            Node::Missing(n) => (
+                n.line,
+                n.column,
+                n.offset,
+                n.end_line,
+                n.end_column,
+                n.end_offset,
+            ),
+        };
+        return Some(SourceRange {
+            start: Position {
+                line,
+                column,
+                offset,
+            },
+            end: Position {
+                line: end_line,
+                column: end_column,
+                offset: end_offset,
+            },
+        });
+    }
+
+    /// Return the name of a tag node.
+    pub fn tag_name(&self) -> Option<&str> {
+        return match self {
+            Node::FunctionTag(n) => Some(&n.name),
+            Node::BlockTag(n) => Some(&n.name),
+            Node::Ignore(n) => Some(&n.name),
+            _ => None,
+        };
+    }
+
+    /// Return the attributes of a tag node.
+    pub fn attributes(&self) -> Option<&Vec<Attribute>> {
+        return match self {
+            Node::FunctionTag(n) => Some(&n.attributes),
+            Node::BlockTag(n) => Some(&n.attributes),
+            Node::Ignore(n) => Some(&n.attributes),
+            _ => None,
+        };
+    }
+
+    /// Return the children of a `Root` or `BlockTag` node.
+    pub fn children(&self) -> Option<&Vec<Node>> {
+        return match self {
+            Node::Root(n) => Some(&n.children),
+            Node::BlockTag(n) => Some(&n.children),
+            _ => None,
+        };
+    }
 }
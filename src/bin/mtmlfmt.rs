@@ -2,15 +2,26 @@ use std::io::Read;
 use clap::Parser;
 
 use mtml_parser::parse;
-use mtml_parser::serializer::{serialize, Options};
+use mtml_parser::serializer::{attribute_order_warnings, serialize_to_writer, AttributeOrder, Options};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let opts = Options::parse();
 
     let mut input = String::new();
     std::io::stdin().read_to_string(&mut input)?;
-    let root = parse(input.as_str())?;
-    print!("{}", serialize(root, Some(opts)));
+    let root = match parse(input.as_str()) {
+        Ok(root) => root,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    if opts.attribute_order != AttributeOrder::Preserve {
+        for warning in attribute_order_warnings(&root) {
+            eprintln!("warning: {}", warning);
+        }
+    }
+    serialize_to_writer(&root, &opts, &mut std::io::stdout().lock())?;
 
     return Ok(());
 }
@@ -0,0 +1,397 @@
+//! AST borrowing its text from the parsed input.
+//!
+//! Mirrors `ast` with `Cow<'a, str>` text fields, so `parser::parse_borrowed`
+//! can build it without copying the input. Convert to the owned AST with
+//! `Node::into_owned` or `From`.
+//!
+//! # Examples
+//!
+//! ```
+//! use mtml_parser::{ast, parser::parse_borrowed};
+//!
+//! let input = String::from("<mt:Entries><$mt:EntryTitle$></mt:Entries>");
+//! let node = parse_borrowed(&input).unwrap();
+//! let names: Vec<_> = node.children().unwrap().iter().filter_map(|n| n.tag_name()).collect();
+//! assert_eq!(names, vec!["Entries"]);
+//!
+//! let node: ast::Node = node.into_owned();
+//! ```
+
+use serde::Serialize;
+use std::borrow::Cow;
+
+use super::ast::{self, AttributeSyntax, QuoteStyle, SourceRange, TagStyle};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AttributeValue<'a> {
+    pub value: Cow<'a, str>,
+    pub quote: QuoteStyle,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<Node<'a>>>,
+    pub line: u32,
+    pub column: usize,
+    pub offset: usize,
+    pub end_line: u32,
+    pub end_column: usize,
+    pub end_offset: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Attribute<'a> {
+    pub name: Cow<'a, str>,
+    pub values: Vec<AttributeValue<'a>>,
+    pub leading_whitespace: Cow<'a, str>,
+    pub syntax: AttributeSyntax,
+    pub line: u32,
+    pub column: usize,
+    pub offset: usize,
+    pub end_line: u32,
+    pub end_column: usize,
+    pub end_offset: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RootNode<'a> {
+    pub children: Vec<Node<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TextNode<'a> {
+    pub value: Cow<'a, str>,
+    pub line: u32,
+    pub column: usize,
+    pub offset: usize,
+    pub end_line: u32,
+    pub end_column: usize,
+    pub end_offset: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FunctionTagNode<'a> {
+    pub name: Cow<'a, str>,
+    pub attributes: Vec<Attribute<'a>>,
+    pub prefix: Cow<'a, str>,
+    pub style: TagStyle,
+    pub tail: Cow<'a, str>,
+    pub line: u32,
+    pub column: usize,
+    pub offset: usize,
+    pub end_line: u32,
+    pub end_column: usize,
+    pub end_offset: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlockTagNode<'a> {
+    pub name: Cow<'a, str>,
+    pub attributes: Vec<Attribute<'a>>,
+    pub children: Vec<Node<'a>>,
+    pub prefix: Cow<'a, str>,
+    pub style: TagStyle,
+    pub tail: Cow<'a, str>,
+    pub closing_prefix: Cow<'a, str>,
+    pub closing_name: Cow<'a, str>,
+    pub line: u32,
+    pub column: usize,
+    pub offset: usize,
+    pub end_line: u32,
+    pub end_column: usize,
+    pub end_offset: usize,
+    pub open_tag: SourceRange,
+    pub body: SourceRange,
+    pub close_tag: Option<SourceRange>,
+    #[serde(skip_serializing_if = "ast::is_false")]
+    pub conditional: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IgnoreNode<'a> {
+    pub name: Cow<'a, str>,
+    pub attributes: Vec<Attribute<'a>>,
+    pub value: Cow<'a, str>,
+    pub prefix: Cow<'a, str>,
+    pub style: TagStyle,
+    pub tail: Cow<'a, str>,
+    pub closing_prefix: Cow<'a, str>,
+    pub closing_name: Cow<'a, str>,
+    pub line: u32,
+    pub column: usize,
+    pub offset: usize,
+    pub end_line: u32,
+    pub end_column: usize,
+    pub end_offset: usize,
+    pub open_tag: SourceRange,
+    pub body: SourceRange,
+    pub close_tag: Option<SourceRange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommentNode<'a> {
+    pub value: Cow<'a, str>,
+    pub line: u32,
+    pub column: usize,
+    pub offset: usize,
+    pub end_line: u32,
+    pub end_column: usize,
+    pub end_offset: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorNode<'a> {
+    pub value: Cow<'a, str>,
+    pub line: u32,
+    pub column: usize,
+    pub offset: usize,
+    pub end_line: u32,
+    pub end_column: usize,
+    pub end_offset: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MissingNode<'a> {
+    pub name: Cow<'a, str>,
+    pub line: u32,
+    pub column: usize,
+    pub offset: usize,
+    pub end_line: u32,
+    pub end_column: usize,
+    pub end_offset: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type")]
+#[allow(clippy::large_enum_variant)]
+pub enum Node<'a> {
+    Root(RootNode<'a>),
+    Text(TextNode<'a>),
+    FunctionTag(FunctionTagNode<'a>),
+    BlockTag(BlockTagNode<'a>),
+    Ignore(IgnoreNode<'a>),
+    Comment(CommentNode<'a>),
+    Error(ErrorNode<'a>),
+    Missing(MissingNode<'a>),
+}
+
+impl<'a> Node<'a> {
+    /// Copy the borrowed text and return the owned AST.
+    pub fn into_owned(self) -> ast::Node {
+        return self.into();
+    }
+
+    /// Return the name of a tag node.
+    pub fn tag_name(&self) -> Option<&str> {
+        return match self {
+            Node::FunctionTag(n) => Some(&n.name),
+            Node::BlockTag(n) => Some(&n.name),
+            Node::Ignore(n) => Some(&n.name),
+            _ => None,
+        };
+    }
+
+    /// Return the attributes of a tag node.
+    pub fn attributes(&self) -> Option<&Vec<Attribute<'a>>> {
+        return match self {
+            Node::FunctionTag(n) => Some(&n.attributes),
+            Node::BlockTag(n) => Some(&n.attributes),
+            Node::Ignore(n) => Some(&n.attributes),
+            _ => None,
+        };
+    }
+
+    /// Return the children of a `Root` or `BlockTag` node.
+    pub fn children(&self) -> Option<&Vec<Node<'a>>> {
+        return match self {
+            Node::Root(n) => Some(&n.children),
+            Node::BlockTag(n) => Some(&n.children),
+            _ => None,
+        };
+    }
+}
+
+fn into_owned_nodes(nodes: Vec<Node>) -> Vec<ast::Node> {
+    return nodes.into_iter().map(ast::Node::from).collect();
+}
+
+fn into_owned_attributes(attributes: Vec<Attribute>) -> Vec<ast::Attribute> {
+    return attributes.into_iter().map(ast::Attribute::from).collect();
+}
+
+impl<'a> From<AttributeValue<'a>> for ast::AttributeValue {
+    fn from(value: AttributeValue<'a>) -> Self {
+        return ast::AttributeValue {
+            value: value.value.into_owned(),
+            quote: value.quote,
+            children: value.children.map(into_owned_nodes),
+            line: value.line,
+            column: value.column,
+            offset: value.offset,
+            end_line: value.end_line,
+            end_column: value.end_column,
+            end_offset: value.end_offset,
+        };
+    }
+}
+
+impl<'a> From<Attribute<'a>> for ast::Attribute {
+    fn from(attribute: Attribute<'a>) -> Self {
+        return ast::Attribute {
+            name: attribute.name.into_owned(),
+            values: attribute
+                .values
+                .into_iter()
+                .map(ast::AttributeValue::from)
+                .collect(),
+            leading_whitespace: attribute.leading_whitespace.into_owned(),
+            syntax: attribute.syntax,
+            line: attribute.line,
+            column: attribute.column,
+            offset: attribute.offset,
+            end_line: attribute.end_line,
+            end_column: attribute.end_column,
+            end_offset: attribute.end_offset,
+            synthetic: false,
+        };
+    }
+}
+
+impl<'a> From<Node<'a>> for ast::Node {
+    fn from(node: Node<'a>) -> Self {
+        return match node {
+            Node::Root(n) => ast::Node::Root(ast::RootNode {
+                children: into_owned_nodes(n.children),
+            }),
+            Node::Text(n) => ast::Node::Text(ast::TextNode {
+                value: n.value.into_owned(),
+                line: n.line,
+                column: n.column,
+                offset: n.offset,
+                end_line: n.end_line,
+                end_column: n.end_column,
+                end_offset: n.end_offset,
+                synthetic: false,
+            }),
+            Node::FunctionTag(n) => ast::Node::FunctionTag(ast::FunctionTagNode {
+                name: n.name.into_owned(),
+                attributes: into_owned_attributes(n.attributes),
+                prefix: n.prefix.into_owned(),
+                style: n.style,
+                tail: n.tail.into_owned(),
+                line: n.line,
+                column: n.column,
+                offset: n.offset,
+                end_line: n.end_line,
+                end_column: n.end_column,
+                end_offset: n.end_offset,
+                synthetic: false,
+            }),
+            Node::BlockTag(n) => ast::Node::BlockTag(ast::BlockTagNode {
+                name: n.name.into_owned(),
+                attributes: into_owned_attributes(n.attributes),
+                children: into_owned_nodes(n.children),
+                prefix: n.prefix.into_owned(),
+                style: n.style,
+                tail: n.tail.into_owned(),
+                closing_prefix: n.closing_prefix.into_owned(),
+                closing_name: n.closing_name.into_owned(),
+                line: n.line,
+                column: n.column,
+                offset: n.offset,
+                end_line: n.end_line,
+                end_column: n.end_column,
+                end_offset: n.end_offset,
+                open_tag: n.open_tag,
+                body: n.body,
+                close_tag: n.close_tag,
+                conditional: n.conditional,
+                synthetic: false,
+            }),
+            Node::Ignore(n) => ast::Node::Ignore(ast::IgnoreNode {
+                name: n.name.into_owned(),
+                attributes: into_owned_attributes(n.attributes),
+                value: n.value.into_owned(),
+                prefix: n.prefix.into_owned(),
+                style: n.style,
+                tail: n.tail.into_owned(),
+                closing_prefix: n.closing_prefix.into_owned(),
+                closing_name: n.closing_name.into_owned(),
+                line: n.line,
+                column: n.column,
+                offset: n.offset,
+                end_line: n.end_line,
+                end_column: n.end_column,
+                end_offset: n.end_offset,
+                open_tag: n.open_tag,
+                body: n.body,
+                close_tag: n.close_tag,
+            }),
+            Node::Comment(n) => ast::Node::Comment(ast::CommentNode {
+                value: n.value.into_owned(),
+                line: n.line,
+                column: n.column,
+                offset: n.offset,
+                end_line: n.end_line,
+                end_column: n.end_column,
+                end_offset: n.end_offset,
+            }),
+            Node::Error(n) => ast::Node::Error(ast::ErrorNode {
+                value: n.value.into_owned(),
+                line: n.line,
+                column: n.column,
+                offset: n.offset,
+                end_line: n.end_line,
+                end_column: n.end_column,
+                end_offset: n.end_offset,
+            }),
+            Node::Missing(n) => ast::Node::Missing(ast::MissingNode {
+                name: n.name.into_owned(),
+                line: n.line,
+                column: n.column,
+                offset: n.offset,
+                end_line: n.end_line,
+                end_column: n.end_column,
+                end_offset: n.end_offset,
+            }),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parser::*;
+    use super::super::serializer::serialize;
+    use super::*;
+
+    #[test]
+    fn test_borrowed_text() {
+        let input = r#"a<mt:Entries lastn="3"><$mt:EntryTitle$></mt:Entries>"#;
+        let node = parse_borrowed(input).unwrap();
+        let children = node.children().unwrap();
+        match &children[0] {
+            Node::Text(text) => assert!(matches!(text.value, Cow::Borrowed("a"))),
+            _ => unreachable!(),
+        }
+        match &children[1] {
+            Node::BlockTag(block) => {
+                assert!(matches!(block.prefix, Cow::Borrowed("mt:")));
+                assert!(matches!(
+                    block.attributes[0].values[0].value,
+                    Cow::Borrowed("3")
+                ));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_into_owned() {
+        let input = r#"<mt:Entries lastn="3"><!-- x --><$mt:Var name="<$mt:EntryID$>"$><mt:Ignore>y</mt:Ignore></mt:Entries>"#;
+        let config = ParserConfig::new().html_comments(true).attribute_tags(true);
+        let parser = config.build();
+        let owned = parser.parse_borrowed(input).unwrap().into_owned();
+        assert_eq!(serialize(owned, None), input);
+        assert_eq!(
+            serde_json::to_string(&parser.parse_borrowed(input).unwrap()).unwrap(),
+            serde_json::to_string(&parser.parse(input).unwrap()).unwrap()
+        );
+    }
+}
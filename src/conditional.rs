@@ -0,0 +1,205 @@
+//! Branches of conditional block tags such as `<mt:If>`.
+//!
+//! The parser keeps `<mt:Else>` and `<mt:ElseIf>` among the children of the
+//! conditional, as function tags or, when written with a closing tag such as
+//! `<mt:Else>...</mt:Else>`, as block tags, so the source can be reproduced as
+//! written.
+//! `BlockTagNode::branches` splits the children at these tags.
+//! `BlockTagNode::conditional` tells whether a block tag is a conditional.
+//!
+//! Else tags outside of conditionals are only validated by
+//! `parse_with_recovery`, which reports each one as a warning. `parse` accepts
+//! them without a diagnostic.
+//!
+//! # Examples
+//!
+//! ```
+//! use mtml_parser::{ast::Node, parse};
+//!
+//! let node = parse(r#"<mt:If name="a">A<mt:ElseIf name="b">B<mt:Else>C</mt:If>"#).unwrap();
+//! if let Node::Root(root) = node {
+//!     if let Node::BlockTag(tag) = &root.children[0] {
+//!         let branches = tag.branches();
+//!         assert_eq!(branches.conditions.len(), 2);
+//!         assert_eq!(branches.conditions[1].attributes[0].values[0].value, "b");
+//!         assert!(branches.else_branch.is_some());
+//!     }
+//! }
+//! ```
+
+use super::ast::{Node::*, *};
+
+/// A branch of a conditional tag: its condition and the nodes used when the
+/// condition holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Branch<'a> {
+    /// The `<mt:Else>` or `<mt:ElseIf>` node starting the branch, either a
+    /// function tag or a block tag, `None` for the branch of the conditional
+    /// tag itself.
+    pub tag: Option<&'a Node>,
+    /// Attributes holding the condition, empty for an `<mt:Else>` branch.
+    pub attributes: &'a [Attribute],
+    pub children: Vec<&'a Node>,
+}
+
+/// Branches of a conditional tag, in source order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Branches<'a> {
+    /// The branch of the conditional tag, followed by one branch for each
+    /// `<mt:ElseIf>` or `<mt:Else>` with attributes.
+    pub conditions: Vec<Branch<'a>>,
+    /// The branch of the first `<mt:Else>` without attributes. Any else tag
+    /// after it is an ordinary child of its branch.
+    pub else_branch: Option<Branch<'a>>,
+}
+
+/// Return whether `node` is an `<mt:Else>` or `<mt:ElseIf>` tag, in function
+/// or block form.
+pub fn is_else_tag(node: &Node) -> bool {
+    return match node {
+        FunctionTag(FunctionTagNode { name, .. }) | BlockTag(BlockTagNode { name, .. }) => {
+            name.eq_ignore_ascii_case("else") || name.eq_ignore_ascii_case("elseif")
+        }
+        _ => false,
+    };
+}
+
+impl BlockTagNode {
+    /// Split the children at `<mt:Else>` and `<mt:ElseIf>` tags.
+    ///
+    /// Only direct children are considered; else tags in nested block tags
+    /// belong to those. A block tag without else tags has a single branch.
+    ///
+    /// An else tag in function form takes the following children, up to the
+    /// next else tag. One in block form, `<mt:Else>...</mt:Else>`, takes its
+    /// own children, and the children following it continue the previous
+    /// branch.
+    pub fn branches(&self) -> Branches<'_> {
+        let mut conditions = vec![Branch {
+            tag: None,
+            attributes: &self.attributes,
+            children: vec![],
+        }];
+        let mut else_branch: Option<Branch> = None;
+        // Index of the condition taking the next children, `None` for the
+        // else branch.
+        let mut current = Some(0);
+        for child in &self.children {
+            if !is_else_tag(child) || else_branch.is_some() {
+                match current {
+                    Some(i) => conditions[i].children.push(child),
+                    None => else_branch.as_mut().unwrap().children.push(child),
+                }
+                continue;
+            }
+
+            let attributes = child.attributes().unwrap();
+            let (children, is_block) = match child {
+                BlockTag(block) => (block.children.iter().collect(), true),
+                _ => (vec![], false),
+            };
+            let branch = Branch {
+                tag: Some(child),
+                attributes,
+                children,
+            };
+            let is_else =
+                child.tag_name().unwrap().eq_ignore_ascii_case("else") && attributes.is_empty();
+            if is_else {
+                else_branch = Some(branch);
+                if !is_block {
+                    current = None;
+                }
+            } else {
+                conditions.push(branch);
+                if !is_block {
+                    current = Some(conditions.len() - 1);
+                }
+            }
+        }
+
+        return Branches {
+            conditions,
+            else_branch,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parser::*;
+    use super::*;
+
+    fn block(node: &Node) -> &BlockTagNode {
+        return match node {
+            Root(RootNode { children }) => match &children[0] {
+                BlockTag(tag) => tag,
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+    }
+
+    fn texts(branch: &Branch) -> Vec<String> {
+        return branch
+            .children
+            .iter()
+            .map(|child| match child {
+                Text(text) => text.value.clone(),
+                child => child.tag_name().unwrap().to_string(),
+            })
+            .collect();
+    }
+
+    #[test]
+    fn test_branches() {
+        let node = parse(
+            r#"<mt:If name="a">A<mt:If name="x">X<mt:Else>Y</mt:If><MTElseIf name="b">B<mt:Else name="c" eq="1">C<$mt:Else$>D<mt:Else>E</mt:If>"#,
+        )
+        .unwrap();
+        let branches = block(&node).branches();
+        assert_eq!(
+            branches
+                .conditions
+                .iter()
+                .map(|branch| (
+                    branch.tag.and_then(|tag| tag.tag_name()),
+                    branch.attributes.len(),
+                    texts(branch)
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (None, 1, vec!["A".to_string(), "If".to_string()]),
+                (Some("ElseIf"), 1, vec!["B".to_string()]),
+                (Some("Else"), 2, vec!["C".to_string()]),
+            ]
+        );
+        let else_branch = branches.else_branch.unwrap();
+        assert!(else_branch.attributes.is_empty());
+        assert_eq!(texts(&else_branch), vec!["D", "Else", "E"]);
+    }
+
+    #[test]
+    fn test_branches_block_form() {
+        let node = parse(
+            r#"<mt:If name="a">A<mt:ElseIf name="b">B</mt:ElseIf>-<mt:Else>C</mt:Else>D<mt:Else>E</mt:Else></mt:If>"#,
+        )
+        .unwrap();
+        let branches = block(&node).branches();
+        assert_eq!(branches.conditions.len(), 2);
+        assert_eq!(texts(&branches.conditions[0]), vec!["A", "-", "D", "Else"]);
+        assert!(matches!(branches.conditions[1].tag, Some(BlockTag(_))));
+        assert_eq!(branches.conditions[1].attributes.len(), 1);
+        assert_eq!(texts(&branches.conditions[1]), vec!["B"]);
+        assert_eq!(texts(&branches.else_branch.unwrap()), vec!["C"]);
+    }
+
+    #[test]
+    fn test_branches_without_else() {
+        let node = parse("<mt:Entries>A</mt:Entries>").unwrap();
+        let branches = block(&node).branches();
+        assert_eq!(branches.conditions.len(), 1);
+        assert_eq!(texts(&branches.conditions[0]), vec!["A"]);
+        assert!(branches.else_branch.is_none());
+    }
+}
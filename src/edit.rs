@@ -0,0 +1,498 @@
+//! Editing API for rewriting an AST.
+//!
+//! Nodes created or changed here are marked `synthetic`. Their positions are
+//! kept from the source node, or zero for new nodes, and no longer describe the
+//! source. Synthetic attribute values are quoted as needed when serialized.
+//! Edited trees can be written out with `serializer::serialize`, also in
+//! lossless mode.
+//!
+//! # Examples
+//!
+//! ```
+//! use mtml_parser::{edit::EditTag, parse, query::query, serialize};
+//!
+//! let mut node = parse("<mt:Entries><$mt:EntryMore$></mt:Entries>").unwrap();
+//! let paths: Vec<_> = query(&node, "EntryMore")
+//!     .unwrap()
+//!     .into_iter()
+//!     .map(|m| m.path)
+//!     .collect();
+//! for path in paths {
+//!     if let Some(tag) = node.node_at_mut(&path).and_then(|node| node.as_tag_mut()) {
+//!         tag.rename("EntryBody");
+//!     }
+//! }
+//! assert_eq!(serialize(node, None), "<mt:Entries><$mt:EntryBody$></mt:Entries>");
+//! ```
+
+use std::ops::Range;
+
+use super::ast::{Node::*, *};
+use super::tag::{looks_conditional, TagKind, CATALOG};
+
+const SYNTHETIC: Position = Position {
+    line: 0,
+    column: 0,
+    offset: 0,
+};
+
+const SYNTHETIC_RANGE: SourceRange = SourceRange {
+    start: SYNTHETIC,
+    end: SYNTHETIC,
+};
+
+impl TextNode {
+    /// Create a synthetic text node.
+    pub fn new(value: &str) -> Self {
+        return TextNode {
+            value: value.to_string(),
+            line: 0,
+            column: 0,
+            offset: 0,
+            end_line: 0,
+            end_column: 0,
+            end_offset: 0,
+            synthetic: true,
+        };
+    }
+}
+
+impl AttributeValue {
+    /// Create a double-quoted attribute value.
+    pub fn new(value: &str) -> Self {
+        return AttributeValue {
+            value: value.to_string(),
+            quote: QuoteStyle::Double,
+            children: None,
+            line: 0,
+            column: 0,
+            offset: 0,
+            end_line: 0,
+            end_column: 0,
+            end_offset: 0,
+        };
+    }
+}
+
+impl Attribute {
+    /// Create a synthetic attribute such as `name="value"`.
+    pub fn new(name: &str, values: &[&str]) -> Self {
+        return Attribute {
+            name: name.to_string(),
+            values: values
+                .iter()
+                .map(|value| AttributeValue::new(value))
+                .collect(),
+            leading_whitespace: " ".to_string(),
+            syntax: AttributeSyntax::Quoted,
+            line: 0,
+            column: 0,
+            offset: 0,
+            end_line: 0,
+            end_column: 0,
+            end_offset: 0,
+            synthetic: true,
+        };
+    }
+}
+
+impl FunctionTagNode {
+    /// Create a synthetic `<$mt:name$>` tag.
+    pub fn new(name: &str, attributes: Vec<Attribute>) -> Self {
+        return FunctionTagNode {
+            name: name.to_string(),
+            attributes,
+            prefix: "mt:".to_string(),
+            style: TagStyle::Dollar,
+            tail: "$".to_string(),
+            line: 0,
+            column: 0,
+            offset: 0,
+            end_line: 0,
+            end_column: 0,
+            end_offset: 0,
+            synthetic: true,
+        };
+    }
+}
+
+impl BlockTagNode {
+    /// Create a synthetic `<mt:name>...</mt:name>` block.
+    pub fn new(name: &str, attributes: Vec<Attribute>, children: Vec<Node>) -> Self {
+        return BlockTagNode {
+            name: name.to_string(),
+            attributes,
+            children,
+            prefix: "mt:".to_string(),
+            style: TagStyle::Plain,
+            tail: "".to_string(),
+            closing_prefix: "mt:".to_string(),
+            closing_name: name.to_string(),
+            line: 0,
+            column: 0,
+            offset: 0,
+            end_line: 0,
+            end_column: 0,
+            end_offset: 0,
+            open_tag: SYNTHETIC_RANGE,
+            body: SYNTHETIC_RANGE,
+            close_tag: Some(SYNTHETIC_RANGE),
+            conditional: is_conditional(name),
+            synthetic: true,
+        };
+    }
+}
+
+/// Classify a tag of a synthetic block by the catalogue, or by its name if
+/// the tag is not catalogued.
+fn is_conditional(name: &str) -> bool {
+    return CATALOG.tag(name).map_or_else(
+        || looks_conditional(name),
+        |tag| tag.kind == TagKind::Conditional,
+    );
+}
+
+/// Editing of a node's children, implemented by `RootNode` and `BlockTagNode`.
+///
+/// Indices out of range panic, as with `Vec`.
+pub trait EditChildren {
+    fn children_mut(&mut self) -> &mut Vec<Node>;
+
+    /// Mark the node as changed.
+    fn mark_synthetic(&mut self);
+
+    fn insert_node(&mut self, index: usize, node: Node) {
+        self.children_mut().insert(index, node);
+        self.mark_synthetic();
+    }
+
+    fn remove_node(&mut self, index: usize) -> Node {
+        self.mark_synthetic();
+        return self.children_mut().remove(index);
+    }
+
+    /// Replace the child at `index`, returning the old child.
+    fn replace_node(&mut self, index: usize, node: Node) -> Node {
+        self.mark_synthetic();
+        return std::mem::replace(&mut self.children_mut()[index], node);
+    }
+
+    /// Move the children in `range` into `block`, after any children it
+    /// already has, and put `block` in their place.
+    fn wrap_nodes(&mut self, range: Range<usize>, mut block: BlockTagNode) {
+        let start = range.start;
+        block.children.extend(self.children_mut().drain(range));
+        block.synthetic = true;
+        self.children_mut().insert(start, BlockTag(block));
+        self.mark_synthetic();
+    }
+
+    /// Replace the block tag at `index` with its children, returning the
+    /// emptied block tag, or `None` if the child is not a block tag.
+    fn unwrap_block(&mut self, index: usize) -> Option<BlockTagNode> {
+        if !matches!(self.children_mut()[index], BlockTag(_)) {
+            return None;
+        }
+        let mut block = match self.children_mut().remove(index) {
+            BlockTag(block) => block,
+            _ => unreachable!(),
+        };
+        let children = std::mem::take(&mut block.children);
+        self.children_mut().splice(index..index, children);
+        self.mark_synthetic();
+        return Some(block);
+    }
+}
+
+impl EditChildren for RootNode {
+    fn children_mut(&mut self) -> &mut Vec<Node> {
+        return &mut self.children;
+    }
+
+    fn mark_synthetic(&mut self) {}
+}
+
+impl EditChildren for BlockTagNode {
+    fn children_mut(&mut self) -> &mut Vec<Node> {
+        return &mut self.children;
+    }
+
+    fn mark_synthetic(&mut self) {
+        self.synthetic = true;
+    }
+}
+
+/// Editing of a tag's name and attributes, implemented by `FunctionTagNode`
+/// and `BlockTagNode`.
+pub trait EditTag {
+    fn name(&self) -> &str;
+
+    fn attributes(&self) -> &Vec<Attribute>;
+
+    fn attributes_mut(&mut self) -> &mut Vec<Attribute>;
+
+    fn set_name(&mut self, name: &str);
+
+    fn mark_synthetic(&mut self);
+
+    /// Rename the tag, keeping the prefix and style it was written with.
+    fn rename(&mut self, name: &str) {
+        self.set_name(name);
+        self.mark_synthetic();
+    }
+
+    /// Return the first attribute named `name`, ignoring case.
+    fn attribute(&self, name: &str) -> Option<&Attribute> {
+        return self
+            .attributes()
+            .iter()
+            .find(|attr| attr.name.eq_ignore_ascii_case(name));
+    }
+
+    /// Set the values of the first attribute named `name`, or append a new
+    /// attribute if there is none. The values keep the quote style of the
+    /// first existing value.
+    fn set_attribute(&mut self, name: &str, values: &[&str]) {
+        let attribute = self
+            .attributes_mut()
+            .iter_mut()
+            .find(|attr| attr.name.eq_ignore_ascii_case(name));
+        match attribute {
+            Some(attribute) => {
+                let quote = attribute
+                    .values
+                    .first()
+                    .map_or(QuoteStyle::Double, |value| value.quote);
+                attribute.values = values
+                    .iter()
+                    .map(|value| AttributeValue {
+                        quote,
+                        ..AttributeValue::new(value)
+                    })
+                    .collect();
+                attribute.syntax = if quote == QuoteStyle::None {
+                    AttributeSyntax::Unquoted
+                } else {
+                    AttributeSyntax::Quoted
+                };
+                attribute.synthetic = true;
+            }
+            None => self.attributes_mut().push(Attribute::new(name, values)),
+        }
+        self.mark_synthetic();
+    }
+
+    /// Remove every attribute named `name`, returning the first one removed.
+    fn remove_attribute(&mut self, name: &str) -> Option<Attribute> {
+        let attributes = self.attributes_mut();
+        let index = attributes
+            .iter()
+            .position(|attr| attr.name.eq_ignore_ascii_case(name))?;
+        let removed = attributes.remove(index);
+        attributes.retain(|attr| !attr.name.eq_ignore_ascii_case(name));
+        self.mark_synthetic();
+        return Some(removed);
+    }
+}
+
+impl EditTag for FunctionTagNode {
+    fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    fn attributes(&self) -> &Vec<Attribute> {
+        return &self.attributes;
+    }
+
+    fn attributes_mut(&mut self) -> &mut Vec<Attribute> {
+        return &mut self.attributes;
+    }
+
+    fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+
+    fn mark_synthetic(&mut self) {
+        self.synthetic = true;
+    }
+}
+
+impl EditTag for BlockTagNode {
+    fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    fn attributes(&self) -> &Vec<Attribute> {
+        return &self.attributes;
+    }
+
+    fn attributes_mut(&mut self) -> &mut Vec<Attribute> {
+        return &mut self.attributes;
+    }
+
+    fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+        self.conditional = is_conditional(name);
+        if !self.closing_name.is_empty() {
+            self.closing_name = name.to_string();
+        }
+    }
+
+    fn mark_synthetic(&mut self) {
+        self.synthetic = true;
+    }
+}
+
+impl Node {
+    /// Return the descendant at `path`, as returned by `query::query`.
+    pub fn node_at_mut(&mut self, path: &[usize]) -> Option<&mut Node> {
+        let mut node = self;
+        for index in path {
+            node = match node {
+                Root(RootNode { children }) | BlockTag(BlockTagNode { children, .. }) => {
+                    children.get_mut(*index)?
+                }
+                _ => return None,
+            };
+        }
+        return Some(node);
+    }
+
+    /// Return the node as an editable tag.
+    pub fn as_tag_mut(&mut self) -> Option<&mut dyn EditTag> {
+        return match self {
+            FunctionTag(node) => Some(node),
+            BlockTag(node) => Some(node),
+            _ => None,
+        };
+    }
+
+    /// Return the node's editable children.
+    pub fn as_children_mut(&mut self) -> Option<&mut dyn EditChildren> {
+        return match self {
+            Root(node) => Some(node),
+            BlockTag(node) => Some(node),
+            _ => None,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parser::*;
+    use super::super::serializer::{serialize, Options};
+    use super::*;
+
+    fn lossless(node: Node) -> String {
+        return serialize(
+            node,
+            Some(Options {
+                lossless: true,
+                ..Default::default()
+            }),
+        );
+    }
+
+    fn root(input: &str) -> RootNode {
+        return match parse(input).unwrap() {
+            Root(root) => root,
+            _ => unreachable!(),
+        };
+    }
+
+    fn root_of(input: &str) -> Node {
+        return parse(input).unwrap();
+    }
+
+    #[test]
+    fn test_edit_children() {
+        let mut root = root("a<MTEntryTitle>b");
+        let old = root.replace_node(1, FunctionTag(FunctionTagNode::new("EntryBody", vec![])));
+        assert!(matches!(old, FunctionTag(_)));
+        root.insert_node(0, Text(TextNode::new("<p>")));
+        assert!(matches!(root.remove_node(3), Text(_)));
+        assert_eq!(lossless(Root(root)), "<p>a<$mt:EntryBody$>");
+    }
+
+    #[test]
+    fn test_wrap_and_unwrap() {
+        let mut root = root("<MTEntries>a<$MTEntryTitle$>b</MTEntries>");
+        let entries = match &mut root.children[0] {
+            BlockTag(entries) => entries,
+            _ => unreachable!(),
+        };
+        entries.wrap_nodes(
+            1..3,
+            BlockTagNode::new("If", vec![Attribute::new("name", &["x"])], vec![]),
+        );
+        assert!(entries.synthetic);
+        assert_eq!(
+            lossless(Root(root)),
+            r#"<MTEntries>a<mt:If name="x"><$MTEntryTitle$>b</mt:If></MTEntries>"#
+        );
+
+        let mut root =
+            root_of(r#"<MTEntries>a<mt:If name="x"><$MTEntryTitle$>b</mt:If></MTEntries>"#);
+        let entries = root.node_at_mut(&[0]).unwrap().as_children_mut().unwrap();
+        assert!(entries.unwrap_block(0).is_none());
+        let block = entries.unwrap_block(1).unwrap();
+        assert_eq!(block.name, "If");
+        assert!(block.conditional);
+        assert!(block.children.is_empty());
+        assert_eq!(lossless(root), "<MTEntries>a<$MTEntryTitle$>b</MTEntries>");
+    }
+
+    #[test]
+    fn test_edit_tag() {
+        let mut node =
+            root_of(r#"<MTEntries lastn='3'><$mt:Var name="a" escape="html"$></MTEntries>"#);
+        let entries = node.node_at_mut(&[0]).unwrap().as_tag_mut().unwrap();
+        entries.rename("Pages");
+        entries.set_attribute("LASTN", &["5"]);
+        assert_eq!(entries.attribute("lastn").unwrap().values[0].value, "5");
+
+        let var = node.node_at_mut(&[0, 0]).unwrap().as_tag_mut().unwrap();
+        assert_eq!(var.remove_attribute("escape").unwrap().name, "escape");
+        assert!(var.remove_attribute("escape").is_none());
+        var.set_attribute("encode_html", &["1"]);
+
+        match &node {
+            Root(RootNode { children }) => match &children[0] {
+                BlockTag(block) => {
+                    assert!(block.synthetic);
+                    assert!(block.attributes[0].synthetic);
+                    assert_eq!(block.line, 1);
+                }
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        }
+        assert_eq!(
+            lossless(node),
+            r#"<MTPages lastn='5'><$mt:Var name="a" encode_html="1"$></MTPages>"#
+        );
+    }
+
+    #[test]
+    fn test_set_attribute_quotes_value() {
+        let mut node = root_of(r#"<$mt:Var name=a value="b"$>"#);
+        let var = node.node_at_mut(&[0]).unwrap().as_tag_mut().unwrap();
+        var.set_attribute("name", &["x y"]);
+        var.set_attribute("value", &[r#"a"b"#]);
+        let serialized = lossless(node);
+        assert_eq!(serialized, r#"<$mt:Var name="x y" value='a"b'$>"#);
+
+        let mut reparsed = root_of(&serialized);
+        let var = reparsed.node_at_mut(&[0]).unwrap().as_tag_mut().unwrap();
+        assert_eq!(var.attribute("name").unwrap().values[0].value, "x y");
+        assert_eq!(var.attribute("value").unwrap().values[0].value, r#"a"b"#);
+    }
+
+    #[test]
+    fn test_node_at_mut() {
+        let mut node = root_of("<mt:Entries><mt:EntryTitle></mt:Entries>");
+        assert!(node.node_at_mut(&[0, 0]).unwrap().as_tag_mut().is_some());
+        assert!(node.node_at_mut(&[0, 1]).is_none());
+        assert!(node.node_at_mut(&[0, 0, 0]).is_none());
+    }
+}
@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::ast::Position;
+
+/// Error returned when an MTML document cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ParseError {
+    /// A block tag such as `<mt:Entries>` is not closed before the end of its parent.
+    UnclosedBlockTag { name: String, position: Position },
+    /// A closing tag does not match the innermost open block tag.
+    MismatchedClosingTag {
+        expected: String,
+        found: String,
+        position: Position,
+    },
+    /// A closing tag appears without a corresponding open block tag.
+    StrayClosingTag { name: String, position: Position },
+    /// A tag is not terminated by `>`.
+    UnterminatedTag { name: String, position: Position },
+    /// A tag name is missing or malformed, e.g. `<mt:>`.
+    InvalidTagName { position: Position },
+    /// An attribute value opened with a quote is never closed.
+    UnterminatedAttributeQuote { name: String, position: Position },
+    /// An attribute name is not followed by `=`.
+    MissingAttributeEquals { name: String, position: Position },
+    /// An `<mt:Else>` or `<mt:ElseIf>` tag is not directly inside a conditional
+    /// tag such as `<mt:If>`. Reported as a warning.
+    MisplacedElse { name: String, position: Position },
+    /// Any other input the parser could not understand.
+    Unexpected { position: Position },
+}
+
+impl ParseError {
+    /// Return the position in the source where the error was detected.
+    pub fn position(&self) -> Position {
+        return match self {
+            ParseError::UnclosedBlockTag { position, .. }
+            | ParseError::MismatchedClosingTag { position, .. }
+            | ParseError::StrayClosingTag { position, .. }
+            | ParseError::UnterminatedTag { position, .. }
+            | ParseError::InvalidTagName { position }
+            | ParseError::UnterminatedAttributeQuote { position, .. }
+            | ParseError::MissingAttributeEquals { position, .. }
+            | ParseError::MisplacedElse { position, .. }
+            | ParseError::Unexpected { position } => *position,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem found while parsing, reported by `parse_with_recovery`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub error: ParseError,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::UnclosedBlockTag { name, position } => {
+                write!(f, "Unclosed block tag mt:{} at {}", name, position)
+            }
+            ParseError::MismatchedClosingTag {
+                expected,
+                found,
+                position,
+            } => write!(
+                f,
+                "Expected closing tag for mt:{} but found mt:{} at {}",
+                expected, found, position
+            ),
+            ParseError::StrayClosingTag { name, position } => {
+                write!(
+                    f,
+                    "Closing tag mt:{} without opening tag at {}",
+                    name, position
+                )
+            }
+            ParseError::UnterminatedTag { name, position } => {
+                write!(f, "Unterminated tag mt:{} at {}", name, position)
+            }
+            ParseError::InvalidTagName { position } => {
+                write!(f, "Invalid tag name at {}", position)
+            }
+            ParseError::UnterminatedAttributeQuote { name, position } => {
+                write!(
+                    f,
+                    "Unterminated quote in attribute {} at {}",
+                    name, position
+                )
+            }
+            ParseError::MissingAttributeEquals { name, position } => {
+                write!(f, "Missing '=' after attribute {} at {}", name, position)
+            }
+            ParseError::MisplacedElse { name, position } => {
+                write!(
+                    f,
+                    "mt:{} outside of a conditional tag at {}",
+                    name, position
+                )
+            }
+            ParseError::Unexpected { position } => {
+                write!(f, "Unexpected input at {}", position)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let error = ParseError::UnclosedBlockTag {
+            name: "Entries".to_string(),
+            position: Position {
+                line: 3,
+                column: 5,
+                offset: 20,
+            },
+        };
+        assert_eq!(
+            error.to_string(),
+            "Unclosed block tag mt:Entries at line 3, column 5"
+        );
+        assert_eq!(error.position().offset, 20);
+    }
+}
@@ -0,0 +1,249 @@
+use serde::{Deserialize, Serialize};
+
+use super::ast::AttributeValue;
+
+/// Meaning of an attribute value, after Movable Type's variable interpolation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Expression {
+    /// Plain text, used as written.
+    Literal { value: String },
+    /// `$name`, replaced by the value of a template variable.
+    Variable { variable: VariableRef },
+    /// `@name`, a template variable passed as an array.
+    Array { variable: VariableRef },
+}
+
+/// Reference to a template variable, e.g. `foo`, `foo{key}` or `foo[1]`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VariableRef {
+    pub name: String,
+    pub index: Option<VariableIndex>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum VariableIndex {
+    /// `foo{key}`, a hash element.
+    Key { key: IndexValue },
+    /// `foo[1]`, an array element.
+    Position { position: IndexValue },
+}
+
+/// Key or position inside `{}` or `[]`, either literal or `$name`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum IndexValue {
+    Literal { value: String },
+    Variable { name: String },
+}
+
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    return match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        }
+        _ => false,
+    };
+}
+
+fn parse_index_value(value: &str) -> Option<IndexValue> {
+    if value.is_empty() {
+        return None;
+    }
+    return Some(match value.strip_prefix('$') {
+        Some(name) if is_variable_name(name) => IndexValue::Variable {
+            name: name.to_string(),
+        },
+        _ => IndexValue::Literal {
+            value: value.to_string(),
+        },
+    });
+}
+
+/// Parse a variable reference such as `foo`, `foo{key}`, `foo[$i]`.
+pub fn parse_variable_ref(value: &str) -> Option<VariableRef> {
+    let (name, index) = match value.find(['{', '[']) {
+        Some(start) => {
+            let index = if value[start..].starts_with('{') {
+                let key = value[start + 1..].strip_suffix('}')?;
+                VariableIndex::Key {
+                    key: parse_index_value(key)?,
+                }
+            } else {
+                let position = value[start + 1..].strip_suffix(']')?;
+                VariableIndex::Position {
+                    position: parse_index_value(position)?,
+                }
+            };
+            (&value[..start], Some(index))
+        }
+        None => (value, None),
+    };
+    if !is_variable_name(name) {
+        return None;
+    }
+
+    return Some(VariableRef {
+        name: name.to_string(),
+        index,
+    });
+}
+
+/// Parse an attribute value the way Movable Type interpolates it.
+///
+/// # Examples
+///
+/// ```
+/// use mtml_parser::expression::{parse_expression, Expression};
+///
+/// assert!(matches!(parse_expression("$limit"), Expression::Variable { .. }));
+/// assert!(matches!(parse_expression("@list"), Expression::Array { .. }));
+/// assert!(matches!(parse_expression("10"), Expression::Literal { .. }));
+/// ```
+pub fn parse_expression(value: &str) -> Expression {
+    if let Some(variable) = value.strip_prefix('$').and_then(parse_variable_ref) {
+        return Expression::Variable { variable };
+    }
+    if let Some(variable) = value.strip_prefix('@').and_then(parse_variable_ref) {
+        return Expression::Array { variable };
+    }
+
+    return Expression::Literal {
+        value: value.to_string(),
+    };
+}
+
+impl AttributeValue {
+    /// Return the value after variable interpolation, e.g. `$limit` in
+    /// `<mt:Entries lastn="$limit">`.
+    pub fn expression(&self) -> Expression {
+        return parse_expression(&self.value);
+    }
+
+    /// Return the value as a variable name, as in `<mt:Var name="foo{key}">`.
+    ///
+    /// A leading `$` is accepted, since Movable Type interpolates it first.
+    pub fn variable(&self) -> Option<VariableRef> {
+        return match self.expression() {
+            Expression::Variable { variable } => Some(variable),
+            Expression::Literal { value } => parse_variable_ref(&value),
+            Expression::Array { .. } => None,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ast::QuoteStyle;
+    use super::*;
+
+    fn variable(name: &str, index: Option<VariableIndex>) -> VariableRef {
+        return VariableRef {
+            name: name.to_string(),
+            index,
+        };
+    }
+
+    #[test]
+    fn test_parse_expression() {
+        assert_eq!(
+            parse_expression("$limit"),
+            Expression::Variable {
+                variable: variable("limit", None)
+            }
+        );
+        assert_eq!(
+            parse_expression("@entry_ids"),
+            Expression::Array {
+                variable: variable("entry_ids", None)
+            }
+        );
+        assert_eq!(
+            parse_expression("$hash{$key}"),
+            Expression::Variable {
+                variable: variable(
+                    "hash",
+                    Some(VariableIndex::Key {
+                        key: IndexValue::Variable {
+                            name: "key".to_string()
+                        }
+                    })
+                )
+            }
+        );
+        for literal in ["10", "$", "$1", "a $b", "user@example.com", "$foo{"] {
+            assert_eq!(
+                parse_expression(literal),
+                Expression::Literal {
+                    value: literal.to_string()
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_variable_ref() {
+        assert_eq!(parse_variable_ref("foo"), Some(variable("foo", None)));
+        assert_eq!(
+            parse_variable_ref("foo[1]"),
+            Some(variable(
+                "foo",
+                Some(VariableIndex::Position {
+                    position: IndexValue::Literal {
+                        value: "1".to_string()
+                    }
+                })
+            ))
+        );
+        assert_eq!(
+            parse_variable_ref("foo{bar baz}"),
+            Some(variable(
+                "foo",
+                Some(VariableIndex::Key {
+                    key: IndexValue::Literal {
+                        value: "bar baz".to_string()
+                    }
+                })
+            ))
+        );
+        for invalid in [
+            "", "1foo", "foo[1}", "foo[]", "foo{a}b", "foo bar", "foo{日",
+        ] {
+            assert_eq!(parse_variable_ref(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_attribute_value_variable() {
+        let value = |value: &str| AttributeValue {
+            value: value.to_string(),
+            quote: QuoteStyle::Double,
+            children: None,
+            line: 1,
+            column: 1,
+            offset: 0,
+            end_line: 1,
+            end_column: 1,
+            end_offset: 0,
+        };
+        assert_eq!(
+            value("foo{a}").variable(),
+            Some(variable(
+                "foo",
+                Some(VariableIndex::Key {
+                    key: IndexValue::Literal {
+                        value: "a".to_string()
+                    }
+                })
+            ))
+        );
+        assert_eq!(value("$foo").variable(), Some(variable("foo", None)));
+        assert_eq!(value("@foo").variable(), None);
+        assert!(matches!(
+            value("foo{a}").expression(),
+            Expression::Literal { .. }
+        ));
+    }
+}
@@ -43,7 +43,7 @@ mod tests {
         let json = to_json(root);
         assert_eq!(
             json,
-            r#"{"children":[{"type":"Text","value":"\n<html>\n  <body>\n    ","line":1,"column":1,"offset":0},{"type":"BlockTag","name":"Entries","attributes":[{"name":"limit","values":[{"value":"10","line":4,"column":26,"offset":42}],"line":4,"column":20,"offset":36}],"children":[{"type":"Text","value":"\n      ","line":4,"column":31,"offset":47},{"type":"FunctionTag","name":"EntryTitle","attributes":[{"name":"encode_html","values":[{"value":"1","line":5,"column":33,"offset":80}],"line":5,"column":21,"offset":68}],"line":5,"column":7,"offset":54},{"type":"Text","value":"\n    ","line":5,"column":38,"offset":85}],"line":4,"column":5,"offset":21},{"type":"Text","value":"\n  </body>\n</html>","line":6,"column":18,"offset":103}]}"#
+            r#"{"children":[{"type":"Text","value":"\n<html>\n  <body>\n    ","line":1,"column":1,"offset":0,"end_line":4,"end_column":5,"end_offset":21},{"type":"BlockTag","name":"Entries","attributes":[{"name":"limit","values":[{"value":"10","quote":"Double","line":4,"column":26,"offset":42,"end_line":4,"end_column":30,"end_offset":46}],"leading_whitespace":"    ","syntax":"Quoted","line":4,"column":20,"offset":36,"end_line":4,"end_column":30,"end_offset":46}],"children":[{"type":"Text","value":"\n      ","line":4,"column":31,"offset":47,"end_line":5,"end_column":7,"end_offset":54},{"type":"FunctionTag","name":"EntryTitle","attributes":[{"name":"encode_html","values":[{"value":"1","quote":"Single","line":5,"column":33,"offset":80,"end_line":5,"end_column":36,"end_offset":83}],"leading_whitespace":" ","syntax":"Quoted","line":5,"column":21,"offset":68,"end_line":5,"end_column":36,"end_offset":83}],"prefix":"mt","style":"SelfClosing","tail":"/","line":5,"column":7,"offset":54,"end_line":5,"end_column":38,"end_offset":85},{"type":"Text","value":"\n    ","line":5,"column":38,"offset":85,"end_line":6,"end_column":5,"end_offset":90}],"prefix":"mt:","style":"Plain","tail":"","closing_prefix":"mt:","closing_name":"Entries","line":4,"column":5,"offset":21,"end_line":6,"end_column":18,"end_offset":103,"open_tag":{"start":{"line":4,"column":5,"offset":21},"end":{"line":4,"column":31,"offset":47}},"body":{"start":{"line":4,"column":31,"offset":47},"end":{"line":6,"column":5,"offset":90}},"close_tag":{"start":{"line":6,"column":5,"offset":90},"end":{"line":6,"column":18,"offset":103}}},{"type":"Text","value":"\n  </body>\n</html>","line":6,"column":18,"offset":103,"end_line":8,"end_column":8,"end_offset":121}]}"#
         )
     }
 }
@@ -1,11 +1,20 @@
 //! # mtml-parser
 
 pub mod ast;
+pub mod borrowed;
+pub mod conditional;
+pub mod edit;
+pub mod error;
+pub mod expression;
 pub mod parser;
+pub mod plugin;
+pub mod query;
 pub mod serializer;
 mod json;
 pub mod tag;
+pub mod visit;
 
-pub use parser::parse;
+pub use error::{Diagnostic, ParseError};
+pub use parser::{parse, parse_with_recovery, Parser, ParserConfig};
 pub use serializer::serialize;
 pub use json::to_json;
@@ -2,22 +2,374 @@ extern crate nom;
 
 use nom::{
     branch::alt,
-    bytes::complete::{is_not, tag, tag_no_case, take_till, take_until},
-    character::{
-        complete::{alpha1, alphanumeric1, anychar, char, multispace0},
-        is_space,
-    },
+    bytes::complete::{tag, tag_no_case, take_until},
+    character::complete::{alpha1, alphanumeric1, anychar, char, multispace0},
     combinator::{opt, recognize},
+    error::ErrorKind,
     multi::many0_count,
     sequence::{pair, tuple},
-    IResult, InputTake,
+    Err, InputTake,
 };
 use nom_locate::{position, LocatedSpan};
+use std::{borrow::Cow, collections::HashSet, path::Path};
 
-use super::ast::{Node::*, *};
-use super::tag::FUNCTION_TAGS;
+use super::ast::{self, AttributeSyntax, Position, QuoteStyle, SourceRange, TagStyle};
+use super::borrowed::{Node::*, *};
+use super::error::{Diagnostic, ParseError, Severity};
+use super::plugin::{load_plugin_config, load_plugin_dir, PluginError, PluginTags};
+use super::tag::{
+    looks_conditional, Edition, MtVersion, TagCatalog, TagKind, CATALOG, FUNCTION_TAGS,
+};
 
 type Span<'a> = LocatedSpan<&'a str>;
+type IResult<'a, O> = nom::IResult<Span<'a>, O, ParseError>;
+
+impl<'a> nom::error::ParseError<Span<'a>> for ParseError {
+    fn from_error_kind(input: Span<'a>, _: ErrorKind) -> Self {
+        return ParseError::Unexpected {
+            position: to_position(&input),
+        };
+    }
+
+    fn append(_: Span<'a>, _: ErrorKind, other: Self) -> Self {
+        return other;
+    }
+}
+
+fn to_cow<'a>(span: &Span<'a>) -> Cow<'a, str> {
+    return Cow::Borrowed(span.fragment());
+}
+
+fn to_position(span: &Span) -> Position {
+    return Position {
+        line: span.location_line(),
+        column: span.get_utf8_column(),
+        offset: span.location_offset(),
+    };
+}
+
+/// Set of tags known to a `Parser`.
+///
+/// Tag names are matched case-insensitively and without prefix.
+///
+/// # Examples
+///
+/// ```
+/// use mtml_parser::parser::ParserConfig;
+///
+/// let parser = ParserConfig::default()
+///     .function_tag("MyPluginTag")
+///     .conditional_tag("IfMyPlugin")
+///     .build();
+/// parser.parse("<mt:IfMyPlugin><mt:MyPluginTag></mt:IfMyPlugin>").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParserConfig {
+    function_tags: HashSet<String>,
+    block_tags: HashSet<String>,
+    conditional_tags: HashSet<String>,
+    html_comments: bool,
+    attribute_tags: bool,
+}
+
+impl Default for ParserConfig {
+    fn default() -> Self {
+        return ParserConfig::new();
+    }
+}
+
+impl ParserConfig {
+    /// Create a configuration that knows the function tags in `tag::FUNCTION_TAGS`
+    /// and the block tags in `tag::CATALOG`.
+    pub fn new() -> Self {
+        let config = ParserConfig {
+            function_tags: FUNCTION_TAGS.lock().unwrap().clone(),
+            ..ParserConfig::empty()
+        };
+        return config.block_tags_of(&CATALOG);
+    }
+
+    /// Create a configuration that knows exactly the tags of a Movable Type
+    /// version and edition.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mtml_parser::parser::ParserConfig;
+    /// use mtml_parser::tag::{Edition, MtVersion};
+    ///
+    /// let config = ParserConfig::for_version(MtVersion::Mt6, Edition::Standard);
+    /// assert!(!config.is_function_tag("ContentID"));
+    /// ```
+    pub fn for_version(version: MtVersion, edition: Edition) -> Self {
+        return ParserConfig::empty().tag_catalog(&TagCatalog::for_version(version, edition));
+    }
+
+    /// Create a configuration that knows no tags at all.
+    pub fn empty() -> Self {
+        return ParserConfig {
+            function_tags: HashSet::new(),
+            block_tags: HashSet::new(),
+            conditional_tags: HashSet::new(),
+            html_comments: false,
+            attribute_tags: false,
+        };
+    }
+
+    /// Register a function tag such as `EntryTitle`.
+    pub fn function_tag(mut self, name: &str) -> Self {
+        let name = name.to_lowercase();
+        self.block_tags.remove(&name);
+        self.conditional_tags.remove(&name);
+        self.function_tags.insert(name);
+        return self;
+    }
+
+    /// Register a block tag such as `Entries`.
+    pub fn block_tag(mut self, name: &str) -> Self {
+        let name = name.to_lowercase();
+        self.function_tags.remove(&name);
+        self.conditional_tags.remove(&name);
+        self.block_tags.insert(name);
+        return self;
+    }
+
+    /// Register a conditional block tag such as `If`, which may contain `<mt:Else>`.
+    pub fn conditional_tag(mut self, name: &str) -> Self {
+        let name = name.to_lowercase();
+        self.function_tags.remove(&name);
+        self.block_tags.insert(name.clone());
+        self.conditional_tags.insert(name);
+        return self;
+    }
+
+    /// Register every tag in a catalogue.
+    pub fn tag_catalog(self, catalog: &TagCatalog) -> Self {
+        let mut config = self;
+        for tag in catalog.tags() {
+            if tag.kind == TagKind::Function {
+                config = config.function_tag(tag.name);
+            }
+        }
+        return config.block_tags_of(catalog);
+    }
+
+    fn block_tags_of(mut self, catalog: &TagCatalog) -> Self {
+        for tag in catalog.tags() {
+            match tag.kind {
+                TagKind::Function => {}
+                TagKind::Block => self = self.block_tag(tag.name),
+                TagKind::Conditional => self = self.conditional_tag(tag.name),
+            }
+        }
+        return self;
+    }
+
+    /// Register the tags declared by a plugin.
+    pub fn plugin_tags(mut self, tags: &PluginTags) -> Self {
+        for name in &tags.function_tags {
+            self = self.function_tag(name);
+        }
+        for name in &tags.block_tags {
+            self = self.block_tag(name);
+        }
+        for name in &tags.conditional_tags {
+            self = self.conditional_tag(name);
+        }
+        return self;
+    }
+
+    /// Register the tags declared in a plugin `config.yaml`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use mtml_parser::parser::ParserConfig;
+    ///
+    /// let parser = ParserConfig::default()
+    ///     .plugin_config("mt/plugins/MyPlugin/config.yaml")?
+    ///     .plugin_dir("mt/addons")?
+    ///     .build();
+    /// # Ok::<(), mtml_parser::plugin::PluginError>(())
+    /// ```
+    pub fn plugin_config<P: AsRef<Path>>(self, path: P) -> Result<Self, PluginError> {
+        let tags = load_plugin_config(path)?;
+        return Ok(self.plugin_tags(&tags));
+    }
+
+    /// Register the tags of every plugin in a plugin directory such as `mt/plugins`.
+    pub fn plugin_dir<P: AsRef<Path>>(self, dir: P) -> Result<Self, PluginError> {
+        let tags = load_plugin_dir(dir)?;
+        return Ok(self.plugin_tags(&tags));
+    }
+
+    /// Parse `<!-- -->` HTML comments as `Comment` nodes, so that MT tags in
+    /// commented-out template code are not parsed as live tags.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mtml_parser::{ast::Node, parser::ParserConfig};
+    ///
+    /// let parser = ParserConfig::default().html_comments(true).build();
+    /// match parser.parse("<!-- <mt:Entries> -->").unwrap() {
+    ///     Node::Root(root) => assert!(matches!(root.children[0], Node::Comment(_))),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn html_comments(mut self, enabled: bool) -> Self {
+        self.html_comments = enabled;
+        return self;
+    }
+
+    /// Parse MT tags in attribute values into `AttributeValue::children`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mtml_parser::{ast::Node, parser::ParserConfig};
+    ///
+    /// let parser = ParserConfig::default().attribute_tags(true).build();
+    /// let node = parser.parse(r#"<$mt:Var name="title" value="<$mt:EntryTitle$>"$>"#);
+    /// match node.unwrap() {
+    ///     Node::Root(root) => match &root.children[0] {
+    ///         Node::FunctionTag(tag) => assert!(tag.attributes[1].values[0].children.is_some()),
+    ///         _ => unreachable!(),
+    ///     },
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn attribute_tags(mut self, enabled: bool) -> Self {
+        self.attribute_tags = enabled;
+        return self;
+    }
+
+    pub fn is_function_tag(&self, name: &str) -> bool {
+        return self.function_tags.contains(&name.to_lowercase());
+    }
+
+    pub fn is_block_tag(&self, name: &str) -> bool {
+        return self.block_tags.contains(&name.to_lowercase());
+    }
+
+    /// Return whether `name` is a conditional tag, which may contain `<mt:Else>`.
+    ///
+    /// A tag that is not registered at all is classified by its name with
+    /// `tag::looks_conditional`.
+    pub fn is_conditional_tag(&self, name: &str) -> bool {
+        let lower = name.to_lowercase();
+        if self.conditional_tags.contains(&lower) {
+            return true;
+        }
+        return !self.block_tags.contains(&lower)
+            && !self.function_tags.contains(&lower)
+            && looks_conditional(name);
+    }
+
+    pub fn build(self) -> Parser {
+        return Parser { config: self };
+    }
+}
+
+/// MTML parser using the tags registered in its `ParserConfig`.
+#[derive(Debug, Clone)]
+pub struct Parser {
+    config: ParserConfig,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        return ParserConfig::new().build();
+    }
+}
+
+impl Parser {
+    pub fn new(config: ParserConfig) -> Self {
+        return Parser { config };
+    }
+
+    pub fn config(&self) -> &ParserConfig {
+        return &self.config;
+    }
+
+    /// Parse MTML document and return AST. See `parse` for details.
+    pub fn parse(&self, input: &str) -> Result<ast::Node, ParseError> {
+        return self.parse_borrowed(input).map(ast::Node::from);
+    }
+
+    /// Parse MTML document and return AST borrowing from `input`. See
+    /// `parse_borrowed` for details.
+    pub fn parse_borrowed<'a>(&self, input: &'a str) -> Result<Node<'a>, ParseError> {
+        match parse_internal(&mut State::new(&self.config, false), Span::new(input)) {
+            Ok((_, (children, _))) => {
+                return Ok(Root(RootNode { children }));
+            }
+            Err(Err::Error(e)) | Err(Err::Failure(e)) => {
+                return Err(e);
+            }
+            Err(Err::Incomplete(_)) => {
+                return Err(ParseError::Unexpected {
+                    position: to_position(&Span::new(input)),
+                });
+            }
+        };
+    }
+
+    /// Parse MTML document, recovering from errors. See `parse_with_recovery` for details.
+    pub fn parse_with_recovery(&self, input: &str) -> (ast::Node, Vec<Diagnostic>) {
+        let mut state = State::new(&self.config, true);
+        let children = match parse_internal(&mut state, Span::new(input)) {
+            Ok((_, (children, _))) => children,
+            Err(Err::Error(e)) | Err(Err::Failure(e)) => {
+                state.report(e);
+                vec![error_node(&Span::new(input))]
+            }
+            Err(Err::Incomplete(_)) => vec![],
+        };
+
+        return (Root(RootNode { children }).into(), state.diagnostics);
+    }
+}
+
+struct State<'c> {
+    config: &'c ParserConfig,
+    recover: bool,
+    diagnostics: Vec<Diagnostic>,
+    open_tags: Vec<(String, Position)>,
+}
+
+impl<'c> State<'c> {
+    fn new(config: &'c ParserConfig, recover: bool) -> Self {
+        return State {
+            config,
+            recover,
+            diagnostics: vec![],
+            open_tags: vec![],
+        };
+    }
+
+    fn report(&mut self, error: ParseError) {
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            error,
+        });
+    }
+
+    fn warn(&mut self, error: ParseError) {
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Warning,
+            error,
+        });
+    }
+}
+
+/// Replace a recoverable nom error with a fatal, more specific `ParseError`.
+fn or_fail<'a, O>(result: IResult<'a, O>, error: impl FnOnce() -> ParseError) -> IResult<'a, O> {
+    return result.map_err(|e| match e {
+        Err::Error(_) => Err::Failure(error()),
+        e => e,
+    });
+}
 
 /// Parse MTML document and return AST.
 ///
@@ -28,114 +380,451 @@ type Span<'a> = LocatedSpan<&'a str>;
 ///
 /// parse("<body><mt:Entries><mt:EntryTitle /></mt:Entries></body>");
 /// ```
-pub fn parse(input: &str) -> Result<Node, String> {
-    match parse_internal(Span::new(input), None) {
-        Ok((_, children)) => {
-            return Ok(Root(RootNode { children }));
-        }
-        Err(e) => {
-            return Err(format!("Parse error: {}", e));
-        }
+///
+/// Errors are reported with the position where they were detected.
+///
+/// ```
+/// use mtml_parser::{parse, ParseError};
+///
+/// match parse("<mt:Entries><mt:EntryTitle />") {
+///     Err(ParseError::UnclosedBlockTag { name, position }) => {
+///         assert_eq!(name, "Entries");
+///         assert_eq!(position.column, 1);
+///     }
+///     _ => unreachable!(),
+/// }
+/// ```
+///
+/// Only errors fail the parse. Warnings, such as an `<mt:Else>` outside of a
+/// conditional tag, are only reported by `parse_with_recovery`.
+pub fn parse(input: &str) -> Result<ast::Node, ParseError> {
+    return Parser::default().parse(input);
+}
+
+/// Parse MTML document and return an AST whose text borrows from `input`.
+///
+/// This avoids copying the input for read-only analyses. See `borrowed` for
+/// the node types and for converting to the owned AST.
+///
+/// # Examples
+///
+/// ```
+/// use mtml_parser::{ast, parser::parse_borrowed};
+///
+/// let node = parse_borrowed("<mt:Entries><mt:EntryTitle /></mt:Entries>").unwrap();
+/// assert_eq!(node.children().unwrap()[0].tag_name(), Some("Entries"));
+/// let node: ast::Node = node.into();
+/// ```
+pub fn parse_borrowed(input: &str) -> Result<Node<'_>, ParseError> {
+    return Parser::default().parse_borrowed(input);
+}
+
+/// Parse MTML document, recovering from errors instead of failing.
+///
+/// The returned AST always covers the whole input. Unclosed block tags get a
+/// `Missing` node where their closing tag was expected, and input that could
+/// not be parsed is kept in `Error` nodes. A broken attribute ends the
+/// attribute list, and the rest of the tag is kept in the tag's `tail`. Each
+/// problem is reported as a `Diagnostic`. An `<mt:Else>` or `<mt:ElseIf>` that
+/// is not directly inside a conditional tag is kept as is and reported as a
+/// warning.
+///
+/// # Examples
+///
+/// ```
+/// use mtml_parser::parser::parse_with_recovery;
+///
+/// let (node, diagnostics) = parse_with_recovery("<mt:Entries><mt:EntryTitle />");
+/// assert_eq!(diagnostics.len(), 1);
+/// ```
+pub fn parse_with_recovery(input: &str) -> (ast::Node, Vec<Diagnostic>) {
+    return Parser::default().parse_with_recovery(input);
+}
+
+/// Split off a broken tag starting at `input`, up to and including the next
+/// `>`, or up to the next `<` if that comes first.
+fn take_broken_tag(input: Span) -> (Span, Span) {
+    let len = match input.fragment()[1..].find(['<', '>']) {
+        Some(index) if input.fragment()[1 + index..].starts_with('>') => index + 2,
+        Some(index) => index + 1,
+        None => input.len(),
     };
+    return input.take_split(len);
 }
 
-fn take_until_tag(input: Span) -> IResult<Span, Span> {
-    let str = input.to_string();
+/// Split off the text before the next MT tag, or before the next terminated
+/// HTML comment if `html_comments` is set.
+fn take_until_tag(input: Span, html_comments: bool) -> IResult<Span> {
+    // Scan bytes: `<`, `$`, `/` and `mt` are ASCII, so every match is a char boundary.
+    let bytes = input.fragment().as_bytes();
     let mut pos = 0usize;
-    loop {
-        match str[pos..].find('<') {
-            Some(index) => {
-                pos += index;
-                let offset = match str.chars().nth(pos + 1) {
-                    Some('$') | Some('/') => 1,
-                    _ => 0,
-                };
-                let next = &str[pos + offset + 1..pos + offset + 3];
-                if next.eq_ignore_ascii_case("mt") {
-                    break;
-                }
-                pos += 1;
-            }
-            None => {
-                pos = str.len();
-                break;
+    while let Some(index) = bytes[pos..].iter().position(|b| *b == b'<') {
+        pos += index;
+        if html_comments && comment_len(&input.fragment()[pos..]).is_some() {
+            return Ok(input.take_split(pos));
+        }
+        let start = match bytes.get(pos + 1) {
+            Some(b'$') | Some(b'/') => pos + 2,
+            _ => pos + 1,
+        };
+        if let Some(next) = bytes.get(start..start + 2) {
+            if next.eq_ignore_ascii_case(b"mt") {
+                return Ok(input.take_split(pos));
             }
         }
+        pos += 1;
     }
 
-    return Ok(input.take_split(pos));
+    return Ok(input.take_split(bytes.len()));
+}
+
+/// Return the length of the HTML comment at the start of `input`, if it is terminated.
+fn comment_len(input: &str) -> Option<usize> {
+    let body = input.strip_prefix("<!--")?;
+    return body.find("-->").map(|index| index + 7);
+}
+
+fn comment_node(input: Span) -> (Span, Node) {
+    let len = comment_len(input.fragment()).unwrap();
+    let (rest, comment) = input.take_split(len);
+    let node = Comment(CommentNode {
+        value: Cow::Borrowed(&comment.fragment()[4..len - 3]),
+        line: comment.location_line(),
+        column: comment.get_utf8_column(),
+        offset: comment.location_offset(),
+        end_line: rest.location_line(),
+        end_column: rest.get_utf8_column(),
+        end_offset: rest.location_offset(),
+    });
+    return (rest, node);
 }
 
+/// Parse a node list until the closing tag of `current_tag` or the end of input.
+///
+/// The returned span is the closing tag, or `None` when the input ended first.
 fn parse_internal<'a>(
+    state: &mut State<'_>,
     mut input: Span<'a>,
-    current_tag: Option<String>,
-) -> IResult<Span<'a>, Vec<Node>> {
+) -> IResult<'a, (Vec<Node<'a>>, Option<ClosingTag<'a>>)> {
     let mut children = vec![];
 
     while input.len() > 0 {
         let (_, pos) = position(input)?;
-        let (rest, text) = match opt(take_until_tag)(input)? {
+        let html_comments = state.config.html_comments;
+        let (rest, text) = match opt(|input| take_until_tag(input, html_comments))(input)? {
             (rest, Some(text)) => (rest, text),
-            _ => (Span::new(""), input),
+            _ => input.take_split(input.len()),
         };
 
         if text.len() > 0 {
             children.push(Text(TextNode {
-                value: text.to_string(),
+                value: to_cow(&text),
                 line: pos.location_line(),
                 column: pos.get_utf8_column(),
                 offset: pos.location_offset(),
+                end_line: rest.location_line(),
+                end_column: rest.get_utf8_column(),
+                end_offset: rest.location_offset(),
             }))
         }
 
+        input = rest;
         if rest.len() == 0 {
             break;
         }
 
         let (_, end_tag) = opt(tag_no_case("</"))(rest)?;
-        if end_tag.is_some() && current_tag.is_some() {
-            let current_tag_str = current_tag.unwrap();
-            let (rest, _) = alt((
-                tag_no_case(format!("</mt:{}>", current_tag_str).as_str()),
-                tag_no_case(format!("</mt{}>", current_tag_str).as_str()),
-            ))(rest)?;
-            input = rest;
-            break;
-        } else {
-            let (rest, node) = parse_tag(rest)?;
+        if html_comments && rest.starts_with("<!--") {
+            let (rest, node) = comment_node(rest);
             children.push(node);
             input = rest;
+        } else if end_tag.is_some() {
+            let (after, closing_tag) = match parse_closing_tag(rest) {
+                Ok(res) => res,
+                Err(Err::Failure(e)) if state.recover => {
+                    state.report(e);
+                    let (after, broken) = take_broken_tag(rest);
+                    children.push(error_node(&broken));
+                    input = after;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            let name = closing_tag.name;
+            if let Some(index) = open_else_tag(&children, &name) {
+                // Block-form `<mt:Else>...</mt:Else>` takes the nodes up to its
+                // closing tag as children.
+                let body = children.split_off(index + 1);
+                let tag = match children.pop() {
+                    Some(FunctionTag(tag)) => tag,
+                    _ => unreachable!(),
+                };
+                children.push(else_block(tag, body, &closing_tag, &after));
+                input = after;
+                continue;
+            }
+            let current_tag = state.open_tags.last().map(|(name, _)| name.clone());
+            match current_tag {
+                Some(current_tag) if current_tag.eq_ignore_ascii_case(&name) => {
+                    return Ok((after, (children, Some(closing_tag))));
+                }
+                Some(current_tag)
+                    if state.recover
+                        && state
+                            .open_tags
+                            .iter()
+                            .any(|(open_tag, _)| open_tag.eq_ignore_ascii_case(&name)) =>
+                {
+                    // Leave the closing tag to the ancestor it belongs to.
+                    close_unclosed_tag(state, &mut children, &current_tag, &closing_tag.span);
+                    return Ok((rest, (children, None)));
+                }
+                current_tag => {
+                    let error = match current_tag {
+                        Some(current_tag) => ParseError::MismatchedClosingTag {
+                            expected: current_tag,
+                            found: name.to_string(),
+                            position: to_position(&closing_tag.span),
+                        },
+                        None => ParseError::StrayClosingTag {
+                            name: name.to_string(),
+                            position: to_position(&closing_tag.span),
+                        },
+                    };
+                    if !state.recover {
+                        return Err(Err::Failure(error));
+                    }
+                    state.report(error);
+                    children.push(error_node(&closing_tag.span));
+                    input = after;
+                }
+            };
+        } else {
+            match parse_tag(state, rest) {
+                Ok((rest, node)) => {
+                    children.push(node);
+                    input = rest;
+                }
+                Err(Err::Error(e)) | Err(Err::Failure(e)) if state.recover => {
+                    state.report(e);
+                    let (after, broken) = take_broken_tag(rest);
+                    children.push(error_node(&broken));
+                    input = after;
+                }
+                Err(e) => return Err(e),
+            };
         };
     }
 
-    return Ok((input, children));
+    if state.recover {
+        if let Some((current_tag, _)) = state.open_tags.last() {
+            let current_tag = current_tag.clone();
+            close_unclosed_tag(state, &mut children, &current_tag, &input);
+        }
+    }
+
+    return Ok((input, (children, None)));
+}
+
+/// Return the index of the last `<mt:Else>` or `<mt:ElseIf>` tag in
+/// `children` that a closing tag named `name` closes.
+fn open_else_tag(children: &[Node], name: &str) -> Option<usize> {
+    if !name.eq_ignore_ascii_case("else") && !name.eq_ignore_ascii_case("elseif") {
+        return None;
+    }
+    return children.iter().rposition(|child| match child {
+        FunctionTag(tag) => tag.style == TagStyle::Plain && tag.name.eq_ignore_ascii_case(name),
+        _ => false,
+    });
+}
+
+fn else_block<'a>(
+    tag: FunctionTagNode<'a>,
+    children: Vec<Node<'a>>,
+    closing_tag: &ClosingTag<'a>,
+    rest: &Span<'a>,
+) -> Node<'a> {
+    let open_tag = SourceRange {
+        start: Position {
+            line: tag.line,
+            column: tag.column,
+            offset: tag.offset,
+        },
+        end: Position {
+            line: tag.end_line,
+            column: tag.end_column,
+            offset: tag.end_offset,
+        },
+    };
+    let close_tag = SourceRange {
+        start: to_position(&closing_tag.span),
+        end: to_position(rest),
+    };
+    return BlockTag(BlockTagNode {
+        name: tag.name,
+        attributes: tag.attributes,
+        children,
+        prefix: tag.prefix,
+        style: tag.style,
+        tail: tag.tail,
+        closing_prefix: to_cow(&closing_tag.prefix),
+        closing_name: to_cow(&closing_tag.name),
+        line: tag.line,
+        column: tag.column,
+        offset: tag.offset,
+        end_line: rest.location_line(),
+        end_column: rest.get_utf8_column(),
+        end_offset: rest.location_offset(),
+        open_tag,
+        body: SourceRange {
+            start: open_tag.end,
+            end: close_tag.start,
+        },
+        close_tag: Some(close_tag),
+        conditional: false,
+    });
+}
+
+fn error_node<'a>(span: &Span<'a>) -> Node<'a> {
+    let end = span.take_split(span.len()).0;
+    return Error(ErrorNode {
+        value: to_cow(span),
+        line: span.location_line(),
+        column: span.get_utf8_column(),
+        offset: span.location_offset(),
+        end_line: end.location_line(),
+        end_column: end.get_utf8_column(),
+        end_offset: end.location_offset(),
+    });
+}
+
+/// Report the innermost open tag as unclosed and mark its closing tag as missing at `at`.
+fn close_unclosed_tag(state: &mut State<'_>, children: &mut Vec<Node>, name: &str, at: &Span) {
+    let position = state.open_tags.last().unwrap().1;
+    state.report(ParseError::UnclosedBlockTag {
+        name: name.to_string(),
+        position,
+    });
+    children.push(Missing(MissingNode {
+        name: Cow::Owned(name.to_string()),
+        line: at.location_line(),
+        column: at.get_utf8_column(),
+        offset: at.location_offset(),
+        end_line: at.location_line(),
+        end_column: at.get_utf8_column(),
+        end_offset: at.location_offset(),
+    }));
+}
+
+struct ClosingTag<'a> {
+    span: Span<'a>,
+    prefix: Span<'a>,
+    name: Span<'a>,
+}
+
+fn parse_closing_tag(input: Span) -> IResult<ClosingTag> {
+    let (rest, prefix) = recognize(pair(tag_no_case("</mt"), opt(char(':'))))(input)?;
+    let (rest, name) = or_fail(name_parser(rest), || ParseError::InvalidTagName {
+        position: to_position(&input),
+    })?;
+    let (rest, _) = or_fail(char('>')(rest), || ParseError::UnterminatedTag {
+        name: name.to_string(),
+        position: to_position(&input),
+    })?;
+
+    return Ok((
+        rest,
+        ClosingTag {
+            span: input.take(input.len() - rest.len()),
+            prefix: prefix.take_split(2).0,
+            name,
+        },
+    ));
+}
+
+/// Return whether `input` starts with an opening (not self-closing) `<mt:Ignore>`.
+fn is_ignore_tag(input: Span) -> bool {
+    let res: IResult<_> = tuple((
+        alt((tag_no_case("<mt"), tag_no_case("<$mt"))),
+        opt(char(':')),
+        name_parser,
+        take_until(">"),
+    ))(input);
+    return match res {
+        Ok((_, (_, _, name, tail))) => {
+            name.eq_ignore_ascii_case("ignore") && !tail.ends_with(['/', '$'])
+        }
+        Err(_) => false,
+    };
+}
+
+/// Split off the raw body of an `<mt:Ignore>` block and its closing tag, which
+/// is `None` if the input ends first. Nested `<mt:Ignore>` blocks are skipped.
+fn take_ignore_body(input: Span) -> IResult<(Span, Option<ClosingTag>)> {
+    let mut depth = 0;
+    let mut rest = input;
+    loop {
+        let (tag, _) = take_until_tag(rest, false)?;
+        if tag.len() == 0 {
+            return Ok((tag, (input, None)));
+        }
+        if let Ok((after, closing_tag)) = parse_closing_tag(tag) {
+            if closing_tag.name.eq_ignore_ascii_case("ignore") {
+                if depth == 0 {
+                    let body = input.take(tag.location_offset() - input.location_offset());
+                    return Ok((after, (body, Some(closing_tag))));
+                }
+                depth -= 1;
+            }
+        } else if is_ignore_tag(tag) {
+            depth += 1;
+        }
+        rest = tag.take_split(1).0;
+    }
 }
 
-fn parse_attribute_values(mut input: Span) -> IResult<Span, Vec<AttributeValue>> {
+fn parse_attribute_values<'a>(
+    state: &mut State<'_>,
+    mut input: Span<'a>,
+    name: &str,
+) -> IResult<'a, Vec<AttributeValue<'a>>> {
     let mut values: Vec<AttributeValue> = vec![];
 
     while input.len() > 0 {
         let (_, pos) = position(input)?;
         let (rest, ch) = opt(alt((char('"'), char('\''))))(input)?;
+        let quote = match ch {
+            Some('"') => QuoteStyle::Double,
+            Some(_) => QuoteStyle::Single,
+            None => QuoteStyle::None,
+        };
         let (rest, value) = match ch {
-            Some(ch) => {
-                let (rest, value) = opt(alt((
-                    recognize(tuple((char('<'), take_till(|c| c != '>'), char('>')))),
-                    is_not(format!("{}\\", ch).as_str()),
-                )))(rest)?;
-                let (rest, _) = char(ch)(rest)?;
-                (rest, value)
-            }
-            None => opt(take_till(|c| is_space(c as u8)))(rest)?,
+            Some(ch) => match take_quoted_value(rest, ch) {
+                Some((rest, value)) => (rest.take_split(1).0, value),
+                None => {
+                    return Err(Err::Failure(ParseError::UnterminatedAttributeQuote {
+                        name: name.to_string(),
+                        position: to_position(&pos),
+                    }))
+                }
+            },
+            None => take_unquoted_value(rest)?,
+        };
+        let children = if state.config.attribute_tags && contains_tag(value) {
+            Some(parse_value_tags(state, value)?.1)
+        } else {
+            None
         };
         values.push(AttributeValue {
-            value: match value {
-                Some(value) => value.to_string(),
-                None => "".to_string(),
-            },
+            value: to_cow(&value),
+            quote,
+            children,
             line: pos.location_line(),
             column: pos.get_utf8_column(),
             offset: pos.location_offset(),
+            end_line: rest.location_line(),
+            end_column: rest.get_utf8_column(),
+            end_offset: rest.location_offset(),
         });
 
         input = rest;
@@ -151,15 +840,85 @@ fn parse_attribute_values(mut input: Span) -> IResult<Span, Vec<AttributeValue>>
     Ok((input, values))
 }
 
-fn name_parser(input: Span) -> IResult<Span, Span> {
+/// Split off a quoted value up to, but not including, the closing `quote`.
+///
+/// As in Movable Type, quotes inside a tag such as `<$mt:Var name="x"$>` do
+/// not end the value. A `<` followed by whitespace does not start a tag. If
+/// skipping tags leaves the value unterminated, the value is searched again
+/// without skipping them. Returns `None` if there is no closing quote at all.
+fn take_quoted_value(input: Span, quote: char) -> Option<(Span, Span)> {
+    let fragment = input.fragment();
+    let find = |skip_tags: bool| {
+        let mut pos = 0;
+        while let Some(c) = fragment[pos..].chars().next() {
+            if c == quote {
+                return Some(pos);
+            }
+            if skip_tags && c == '<' {
+                let tag = &fragment[pos + 1..];
+                match tag.find('>') {
+                    Some(len) if len > 0 && !tag.starts_with(char::is_whitespace) => {
+                        pos += len + 2;
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+            pos += c.len_utf8();
+        }
+        return None;
+    };
+    return find(true)
+        .or_else(|| find(false))
+        .map(|len| input.take_split(len));
+}
+
+fn contains_tag(value: Span) -> bool {
+    return match take_until_tag(value, false) {
+        Ok((rest, _)) => rest.len() > 0,
+        Err(_) => false,
+    };
+}
+
+/// Parse the MT tags in an attribute value, which must be balanced within the value.
+fn parse_value_tags<'a>(state: &mut State<'_>, value: Span<'a>) -> IResult<'a, Vec<Node<'a>>> {
+    let open_tags = std::mem::take(&mut state.open_tags);
+    let res = parse_internal(state, value);
+    state.open_tags = open_tags;
+    return match res {
+        Ok((rest, (children, _))) => Ok((rest, children)),
+        Err(Err::Error(e)) => Err(Err::Failure(e)),
+        Err(e) => Err(e),
+    };
+}
+
+/// Split off an unquoted value, which ends at whitespace, `>`, `/>` or `$>`.
+fn take_unquoted_value(input: Span) -> IResult<Span> {
+    let fragment = input.fragment();
+    let len = fragment
+        .char_indices()
+        .find(|(i, c)| c.is_whitespace() || is_tag_end(&fragment[*i..]))
+        .map(|(i, _)| i)
+        .unwrap_or(input.len());
+    return Ok(input.take_split(len));
+}
+
+fn is_tag_end(input: &str) -> bool {
+    return input.starts_with('>') || input.starts_with("$>") || input.starts_with("/>");
+}
+
+fn name_parser(input: Span) -> IResult<Span> {
     recognize(pair(
         alt((alpha1, tag("_"))),
         many0_count(alt((alphanumeric1, tag("_"), tag(":")))),
     ))(input)
 }
 
-fn parse_attribute(input: Span) -> IResult<Span, Option<Attribute>> {
-    let (rest, _) = multispace0(input)?;
+fn parse_attribute<'a>(
+    state: &mut State<'_>,
+    input: Span<'a>,
+) -> IResult<'a, Option<Attribute<'a>>> {
+    let (rest, whitespace) = multispace0(input)?;
     let (_, pos) = position(rest)?;
 
     let (rest, name) = opt(name_parser)(rest)?;
@@ -168,26 +927,63 @@ fn parse_attribute(input: Span) -> IResult<Span, Option<Attribute>> {
         None => return Ok((input, None)),
     };
 
-    let (rest, _) = char('=')(rest)?;
-    let (rest, values) = parse_attribute_values(rest)?;
+    let (rest, values, syntax) = match opt(char('='))(rest)? {
+        (rest, Some(_)) => {
+            let (rest, values) = parse_attribute_values(state, rest, &name)?;
+            let syntax = if values.iter().all(|v| v.quote != QuoteStyle::None) {
+                AttributeSyntax::Quoted
+            } else {
+                AttributeSyntax::Unquoted
+            };
+            (rest, values, syntax)
+        }
+        (rest, None)
+            if rest.is_empty()
+                || rest.starts_with(char::is_whitespace)
+                || is_tag_end(rest.fragment()) =>
+        {
+            (rest, vec![], AttributeSyntax::Valueless)
+        }
+        _ => {
+            return Err(Err::Failure(ParseError::MissingAttributeEquals {
+                name: name.to_string(),
+                position: to_position(&pos),
+            }))
+        }
+    };
 
     return Ok((
         rest,
         Some(Attribute {
-            name: name.to_string(),
+            name: to_cow(&name),
             values,
+            leading_whitespace: to_cow(&whitespace),
+            syntax,
             line: pos.location_line(),
             column: pos.get_utf8_column(),
             offset: pos.location_offset(),
+            end_line: rest.location_line(),
+            end_column: rest.get_utf8_column(),
+            end_offset: rest.location_offset(),
         }),
     ));
 }
 
-fn parse_attributes(mut input: Span) -> IResult<Span, Vec<Attribute>> {
+fn parse_attributes<'a>(
+    state: &mut State<'_>,
+    mut input: Span<'a>,
+) -> IResult<'a, Vec<Attribute<'a>>> {
     let mut attributes = vec![];
 
     loop {
-        let (rest, attribute) = parse_attribute(input)?;
+        let (rest, attribute) = match parse_attribute(state, input) {
+            Err(Err::Failure(e)) if state.recover => {
+                // Keep the rest of the tag verbatim in its tail.
+                state.report(e);
+                break;
+            }
+            res => res?,
+        };
         match attribute {
             Some(attribute) => {
                 input = rest;
@@ -200,56 +996,188 @@ fn parse_attributes(mut input: Span) -> IResult<Span, Vec<Attribute>> {
     return Ok((input, attributes));
 }
 
-fn parse_tag(input: Span) -> IResult<Span, Node> {
+fn parse_tag<'a>(state: &mut State<'_>, input: Span<'a>) -> IResult<'a, Node<'a>> {
     let (_, pos) = position(input)?;
     let (rest, head) = alt((tag_no_case("<mt"), tag_no_case("<$mt")))(input)?;
     let (rest, _) = opt(char(':'))(rest)?;
-    let (rest, name) = name_parser(rest)?;
-    let (rest, attributes) = parse_attributes(rest)?;
-    let (rest, tail) = take_until(">")(rest)?;
+    let prefix = Cow::Borrowed(&input.fragment()[head.len() - 2..input.len() - rest.len()]);
+    let (rest, name) = or_fail(name_parser(rest), || ParseError::InvalidTagName {
+        position: to_position(&pos),
+    })?;
+    let (rest, attributes) = parse_attributes(state, rest)?;
+    let (rest, tail) = or_fail(take_until(">")(rest), || ParseError::UnterminatedTag {
+        name: name.to_string(),
+        position: to_position(&pos),
+    })?;
     let (rest, _) = anychar(rest)?;
+    let style = if head.starts_with("<$") {
+        TagStyle::Dollar
+    } else if tail.ends_with('/') {
+        TagStyle::SelfClosing
+    } else {
+        TagStyle::Plain
+    };
 
-    if FUNCTION_TAGS.lock().unwrap().contains(&name.to_lowercase())
+    if state.config.is_function_tag(&name)
         || &name.to_lowercase() == "else"
-        || (tail.len() >= 1
-            && (head.chars().nth(1).unwrap() == '$' || tail.chars().rev().nth(0).unwrap() == '/'))
+        || (tail.len() >= 1 && (head.chars().nth(1).unwrap() == '$' || tail.ends_with('/')))
     {
+        let is_else = name.eq_ignore_ascii_case("else") || name.eq_ignore_ascii_case("elseif");
+        let in_conditional = state
+            .open_tags
+            .last()
+            .is_some_and(|(open_tag, _)| state.config.is_conditional_tag(open_tag));
+        if state.recover && is_else && !in_conditional {
+            state.warn(ParseError::MisplacedElse {
+                name: name.to_string(),
+                position: to_position(&pos),
+            });
+        }
         return Ok((
             rest,
             FunctionTag(FunctionTagNode {
-                name: name.to_string(),
+                name: to_cow(&name),
                 attributes,
+                prefix,
+                style,
+                tail: to_cow(&tail),
                 line: pos.location_line(),
                 column: pos.get_utf8_column(),
                 offset: pos.location_offset(),
+                end_line: rest.location_line(),
+                end_column: rest.get_utf8_column(),
+                end_offset: rest.location_offset(),
             }),
         ));
-    } else {
-        let (rest, children) = parse_internal(rest, Some(name.to_string()))?;
+    } else if name.eq_ignore_ascii_case("ignore") {
+        let open_tag = SourceRange {
+            start: to_position(&pos),
+            end: to_position(&rest),
+        };
+        let (rest, (value, closing_tag)) = take_ignore_body(rest)?;
+        if closing_tag.is_none() {
+            let error = ParseError::UnclosedBlockTag {
+                name: name.to_string(),
+                position: to_position(&pos),
+            };
+            if !state.recover {
+                return Err(Err::Failure(error));
+            }
+            state.report(error);
+        }
+        let close_tag = closing_tag.as_ref().map(|closing_tag| SourceRange {
+            start: to_position(&closing_tag.span),
+            end: to_position(&rest),
+        });
+        let body = SourceRange {
+            start: open_tag.end,
+            end: match close_tag {
+                Some(close_tag) => close_tag.start,
+                None => to_position(&rest),
+            },
+        };
         return Ok((
             rest,
-            BlockTag(BlockTagNode {
-                name: name.to_string(),
-                children,
+            Ignore(IgnoreNode {
+                name: to_cow(&name),
                 attributes,
+                value: to_cow(&value),
+                prefix,
+                style,
+                tail: to_cow(&tail),
+                closing_prefix: match &closing_tag {
+                    Some(closing_tag) => to_cow(&closing_tag.prefix),
+                    None => Cow::Borrowed(""),
+                },
+                closing_name: match &closing_tag {
+                    Some(closing_tag) => to_cow(&closing_tag.name),
+                    None => Cow::Borrowed(""),
+                },
                 line: pos.location_line(),
                 column: pos.get_utf8_column(),
                 offset: pos.location_offset(),
+                end_line: rest.location_line(),
+                end_column: rest.get_utf8_column(),
+                end_offset: rest.location_offset(),
+                open_tag,
+                body,
+                close_tag,
             }),
         ));
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_parse_blank_attribute() {
-        let (rest, tag) = parse_tag(Span::new(r#"<$mt:Var name="search_link" strip="" trim="1" encode_html="1" setvar="search_link"$>"#)).unwrap();
+    } else {
+        let open_tag = SourceRange {
+            start: to_position(&pos),
+            end: to_position(&rest),
+        };
+        state.open_tags.push((name.to_string(), to_position(&pos)));
+        let res = parse_internal(state, rest);
+        state.open_tags.pop();
+        let (rest, (children, closing_tag)) = res?;
+        if closing_tag.is_none() && !state.recover {
+            return Err(Err::Failure(ParseError::UnclosedBlockTag {
+                name: name.to_string(),
+                position: to_position(&pos),
+            }));
+        }
+        let close_tag = closing_tag.as_ref().map(|closing_tag| SourceRange {
+            start: to_position(&closing_tag.span),
+            end: to_position(&rest),
+        });
+        let body = SourceRange {
+            start: open_tag.end,
+            end: match close_tag {
+                Some(close_tag) => close_tag.start,
+                None => to_position(&rest),
+            },
+        };
+        return Ok((
+            rest,
+            BlockTag(BlockTagNode {
+                name: to_cow(&name),
+                children,
+                attributes,
+                prefix,
+                style,
+                tail: to_cow(&tail),
+                closing_prefix: match &closing_tag {
+                    Some(closing_tag) => to_cow(&closing_tag.prefix),
+                    None => Cow::Borrowed(""),
+                },
+                closing_name: match &closing_tag {
+                    Some(closing_tag) => to_cow(&closing_tag.name),
+                    None => Cow::Borrowed(""),
+                },
+                line: pos.location_line(),
+                column: pos.get_utf8_column(),
+                offset: pos.location_offset(),
+                end_line: rest.location_line(),
+                end_column: rest.get_utf8_column(),
+                end_offset: rest.location_offset(),
+                open_tag,
+                body,
+                close_tag,
+                conditional: state.config.is_conditional_tag(&name),
+            }),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ast::{
+        Attribute, AttributeValue, BlockTagNode, ErrorNode, FunctionTagNode, IgnoreNode,
+        MissingNode,
+        Node::{self, BlockTag, Comment, Error, FunctionTag, Ignore, Missing, Root, Text},
+        RootNode, TextNode,
+    };
+    use super::*;
+
+    #[test]
+    fn test_parse_blank_attribute() {
+        let (rest, tag) = parse_tag(&mut State::new(&ParserConfig::default(), false), Span::new(r#"<$mt:Var name="search_link" strip="" trim="1" encode_html="1" setvar="search_link"$>"#)).unwrap();
         assert_eq!(*rest.fragment(), "");
         assert_eq!(
-            tag,
+            Node::from(tag),
             FunctionTag(FunctionTagNode {
                 name: "Var".to_string(),
                 attributes: vec![
@@ -257,79 +1185,141 @@ mod tests {
                         name: "name".to_string(),
                         values: vec![AttributeValue {
                             value: "search_link".to_string(),
+                            quote: QuoteStyle::Double,
+                            children: None,
                             line: 1,
                             column: 15,
                             offset: 14,
+                            end_line: 1,
+                            end_column: 28,
+                            end_offset: 27,
                         }],
+                        leading_whitespace: " ".to_string(),
+                        syntax: AttributeSyntax::Quoted,
                         line: 1,
                         column: 10,
                         offset: 9,
+                        end_line: 1,
+                        end_column: 28,
+                        end_offset: 27,
+                        synthetic: false,
                     },
                     Attribute {
                         name: "strip".to_string(),
                         values: vec![AttributeValue {
                             value: "".to_string(),
+                            quote: QuoteStyle::Double,
+                            children: None,
                             line: 1,
                             column: 35,
                             offset: 34,
+                            end_line: 1,
+                            end_column: 37,
+                            end_offset: 36,
                         }],
+                        leading_whitespace: " ".to_string(),
+                        syntax: AttributeSyntax::Quoted,
                         line: 1,
                         column: 29,
                         offset: 28,
+                        end_line: 1,
+                        end_column: 37,
+                        end_offset: 36,
+                        synthetic: false,
                     },
                     Attribute {
                         name: "trim".to_string(),
                         values: vec![AttributeValue {
                             value: "1".to_string(),
+                            quote: QuoteStyle::Double,
+                            children: None,
                             line: 1,
                             column: 43,
                             offset: 42,
+                            end_line: 1,
+                            end_column: 46,
+                            end_offset: 45,
                         }],
+                        leading_whitespace: " ".to_string(),
+                        syntax: AttributeSyntax::Quoted,
                         line: 1,
                         column: 38,
                         offset: 37,
+                        end_line: 1,
+                        end_column: 46,
+                        end_offset: 45,
+                        synthetic: false,
                     },
                     Attribute {
                         name: "encode_html".to_string(),
                         values: vec![AttributeValue {
                             value: "1".to_string(),
+                            quote: QuoteStyle::Double,
+                            children: None,
                             line: 1,
                             column: 59,
                             offset: 58,
+                            end_line: 1,
+                            end_column: 62,
+                            end_offset: 61,
                         }],
+                        leading_whitespace: " ".to_string(),
+                        syntax: AttributeSyntax::Quoted,
                         line: 1,
                         column: 47,
                         offset: 46,
+                        end_line: 1,
+                        end_column: 62,
+                        end_offset: 61,
+                        synthetic: false,
                     },
                     Attribute {
                         name: "setvar".to_string(),
                         values: vec![AttributeValue {
                             value: "search_link".to_string(),
+                            quote: QuoteStyle::Double,
+                            children: None,
                             line: 1,
                             column: 70,
                             offset: 69,
+                            end_line: 1,
+                            end_column: 83,
+                            end_offset: 82,
                         }],
+                        leading_whitespace: " ".to_string(),
+                        syntax: AttributeSyntax::Quoted,
                         line: 1,
                         column: 63,
                         offset: 62,
+                        end_line: 1,
+                        end_column: 83,
+                        end_offset: 82,
+                        synthetic: false,
                     },
                 ],
+                prefix: "mt:".to_string(),
+                style: TagStyle::Dollar,
+                tail: "$".to_string(),
                 line: 1,
                 column: 1,
-                offset: 0
+                offset: 0,
+                end_line: 1,
+                end_column: 85,
+                end_offset: 84,
+                synthetic: false,
             })
         );
     }
 
     #[test]
     fn test_parse_if_else() {
-        let (rest, tag) = parse_tag(Span::new(
+        let (rest, tag) = parse_tag(&mut State::new(&ParserConfig::default(), false), Span::new(
             r#"<mt:If name="blog_lang" eq="ja">ja_JP<mt:else><$mt:Var name="blog_lang"$></mt:If>"#,
         ))
         .unwrap();
         assert_eq!(*rest.fragment(), "");
         assert_eq!(
-            tag,
+            Node::from(tag),
             BlockTag(BlockTagNode {
                 name: "If".to_string(),
                 attributes: vec![
@@ -337,43 +1327,122 @@ mod tests {
                         name: "name".to_string(),
                         values: vec![AttributeValue {
                             value: "blog_lang".to_string(),
+                            quote: QuoteStyle::Double,
+                            children: None,
                             line: 1,
                             column: 13,
                             offset: 12,
+                            end_line: 1,
+                            end_column: 24,
+                            end_offset: 23,
                         }],
+                        leading_whitespace: " ".to_string(),
+                        syntax: AttributeSyntax::Quoted,
                         line: 1,
                         column: 8,
                         offset: 7,
+                        end_line: 1,
+                        end_column: 24,
+                        end_offset: 23,
+                        synthetic: false,
                     },
                     Attribute {
                         name: "eq".to_string(),
                         values: vec![AttributeValue {
                             value: "ja".to_string(),
+                            quote: QuoteStyle::Double,
+                            children: None,
                             line: 1,
                             column: 28,
                             offset: 27,
+                            end_line: 1,
+                            end_column: 32,
+                            end_offset: 31,
                         }],
+                        leading_whitespace: " ".to_string(),
+                        syntax: AttributeSyntax::Quoted,
                         line: 1,
                         column: 25,
                         offset: 24,
+                        end_line: 1,
+                        end_column: 32,
+                        end_offset: 31,
+                        synthetic: false,
                     },
                 ],
+                prefix: "mt:".to_string(),
+                style: TagStyle::Plain,
+                tail: "".to_string(),
+                closing_prefix: "mt:".to_string(),
+                closing_name: "If".to_string(),
                 line: 1,
                 column: 1,
                 offset: 0,
+                end_line: 1,
+                end_column: 82,
+                end_offset: 81,
+                conditional: true,
+                synthetic: false,
+                open_tag: SourceRange {
+                    start: Position {
+                        line: 1,
+                        column: 1,
+                        offset: 0,
+                    },
+                    end: Position {
+                        line: 1,
+                        column: 33,
+                        offset: 32,
+                    },
+                },
+                body: SourceRange {
+                    start: Position {
+                        line: 1,
+                        column: 33,
+                        offset: 32,
+                    },
+                    end: Position {
+                        line: 1,
+                        column: 74,
+                        offset: 73,
+                    },
+                },
+                close_tag: Some(SourceRange {
+                    start: Position {
+                        line: 1,
+                        column: 74,
+                        offset: 73,
+                    },
+                    end: Position {
+                        line: 1,
+                        column: 82,
+                        offset: 81,
+                    },
+                },),
                 children: vec![
                     Text(TextNode {
                         value: "ja_JP".to_string(),
                         line: 1,
                         column: 33,
                         offset: 32,
+                        end_line: 1,
+                        end_column: 38,
+                        end_offset: 37,
+                        synthetic: false,
                     }),
                     FunctionTag(FunctionTagNode {
                         name: "else".to_string(),
                         attributes: vec![],
+                        prefix: "mt:".to_string(),
+                        style: TagStyle::Plain,
+                        tail: "".to_string(),
                         line: 1,
                         column: 38,
                         offset: 37,
+                        end_line: 1,
+                        end_column: 47,
+                        end_offset: 46,
+                        synthetic: false,
                     }),
                     FunctionTag(FunctionTagNode {
                         name: "Var".to_string(),
@@ -381,95 +1450,878 @@ mod tests {
                             name: "name".to_string(),
                             values: vec![AttributeValue {
                                 value: "blog_lang".to_string(),
+                                quote: QuoteStyle::Double,
+                                children: None,
                                 line: 1,
                                 column: 61,
                                 offset: 60,
+                                end_line: 1,
+                                end_column: 72,
+                                end_offset: 71,
                             }],
+                            leading_whitespace: " ".to_string(),
+                            syntax: AttributeSyntax::Quoted,
                             line: 1,
                             column: 56,
                             offset: 55,
+                            end_line: 1,
+                            end_column: 72,
+                            end_offset: 71,
+                            synthetic: false,
                         }],
+                        prefix: "mt:".to_string(),
+                        style: TagStyle::Dollar,
+                        tail: "$".to_string(),
                         line: 1,
                         column: 47,
                         offset: 46,
+                        end_line: 1,
+                        end_column: 74,
+                        end_offset: 73,
+                        synthetic: false,
                     }),
                 ],
             })
         );
     }
 
+    #[test]
+    fn test_parse_block_form_else() {
+        let input = r#"<mt:If name="a">A<mt:ElseIf name="b">B</mt:ElseIf><MTElse>C<mt:If name="c">D<mt:Else>E</mt:If></MTElse></mt:If>"#;
+        let root = parse(input).unwrap();
+        let children = match root.children().map(|children| &children[..]) {
+            Some([BlockTag(BlockTagNode { children, .. })]) => children,
+            res => panic!("unexpected children: {:?}", res),
+        };
+        let summary = children
+            .iter()
+            .map(|child| match child {
+                Text(text) => (text.value.as_str(), 0),
+                BlockTag(tag) => (tag.name.as_str(), tag.children.len()),
+                child => panic!("unexpected child: {:?}", child),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(summary, vec![("A", 0), ("ElseIf", 1), ("Else", 2)]);
+        match &children[2] {
+            BlockTag(tag) => {
+                assert_eq!(tag.closing_prefix, "MT");
+                assert_eq!(tag.body.start, position(1, 59, 58));
+                assert_eq!(tag.close_tag.unwrap().start, position(1, 95, 94));
+            }
+            _ => unreachable!(),
+        }
+
+        let (root, diagnostics) = parse_with_recovery(input);
+        assert!(diagnostics.is_empty());
+        let serialized = crate::serializer::serialize(
+            root,
+            Some(crate::serializer::Options {
+                lossless: true,
+                ..Default::default()
+            }),
+        );
+        assert_eq!(serialized, input);
+
+        assert!(matches!(
+            parse("<mt:Entries><$mt:Else$>A</mt:Else></mt:Entries>"),
+            Err(ParseError::MismatchedClosingTag { .. })
+        ));
+    }
+
     #[test]
     fn test_parse_tag_function_tag() {
-        let (rest, tag) = parse_tag(Span::new(r#"<mt:EntryTitle>"#)).unwrap();
+        let (rest, tag) = parse_tag(
+            &mut State::new(&ParserConfig::default(), false),
+            Span::new(r#"<mt:EntryTitle>"#),
+        )
+        .unwrap();
         assert_eq!(*rest.fragment(), "");
         assert_eq!(
-            tag,
+            Node::from(tag),
             FunctionTag(FunctionTagNode {
                 name: "EntryTitle".to_string(),
                 attributes: vec![],
+                prefix: "mt:".to_string(),
+                style: TagStyle::Plain,
+                tail: "".to_string(),
                 line: 1,
                 column: 1,
-                offset: 0
+                offset: 0,
+                end_line: 1,
+                end_column: 16,
+                end_offset: 15,
+                synthetic: false,
             })
         );
     }
 
     #[test]
     fn test_parse_attribute() {
-        let (rest, attribute) = parse_attribute(Span::new(r#"limit="10""#)).unwrap();
+        let (rest, attribute) = parse_attribute(
+            &mut State::new(&ParserConfig::default(), false),
+            Span::new(r#"limit="10""#),
+        )
+        .unwrap();
         assert_eq!(*rest.fragment(), "");
-        let attribute = attribute.unwrap();
+        let attribute = Attribute::from(attribute.unwrap());
         assert_eq!(attribute.name, "limit");
         assert_eq!(
             attribute.values,
             vec![AttributeValue {
                 value: "10".to_string(),
+                quote: QuoteStyle::Double,
+                children: None,
                 line: 1,
                 column: 7,
-                offset: 6
+                offset: 6,
+                end_line: 1,
+                end_column: 11,
+                end_offset: 10,
             }]
         );
     }
 
     #[test]
     fn test_parse_attribute_single_quote() {
-        let (rest, attribute) = parse_attribute(Span::new(r#"limit='10'"#)).unwrap();
+        let (rest, attribute) = parse_attribute(
+            &mut State::new(&ParserConfig::default(), false),
+            Span::new(r#"limit='10'"#),
+        )
+        .unwrap();
         assert_eq!(*rest.fragment(), "");
-        let attribute = attribute.unwrap();
+        let attribute = Attribute::from(attribute.unwrap());
         assert_eq!(attribute.name, "limit");
         assert_eq!(
             attribute.values,
             vec![AttributeValue {
                 value: "10".to_string(),
+                quote: QuoteStyle::Single,
+                children: None,
                 line: 1,
                 column: 7,
-                offset: 6
+                offset: 6,
+                end_line: 1,
+                end_column: 11,
+                end_offset: 10,
             }]
         );
     }
 
     #[test]
     fn test_parse_attribute_replace() {
-        let (rest, attribute) = parse_attribute(Span::new(r#"replace="a","b""#)).unwrap();
+        let (rest, attribute) = parse_attribute(
+            &mut State::new(&ParserConfig::default(), false),
+            Span::new(r#"replace="a","b""#),
+        )
+        .unwrap();
         assert_eq!(*rest.fragment(), "");
-        let attribute = attribute.unwrap();
+        let attribute = Attribute::from(attribute.unwrap());
         assert_eq!(attribute.name, "replace");
         assert_eq!(
             attribute.values,
             vec![
                 AttributeValue {
                     value: "a".to_string(),
+                    quote: QuoteStyle::Double,
+                    children: None,
                     line: 1,
                     column: 9,
-                    offset: 8
+                    offset: 8,
+                    end_line: 1,
+                    end_column: 12,
+                    end_offset: 11,
                 },
                 AttributeValue {
                     value: "b".to_string(),
+                    quote: QuoteStyle::Double,
+                    children: None,
                     line: 1,
                     column: 13,
-                    offset: 12
+                    offset: 12,
+                    end_line: 1,
+                    end_column: 16,
+                    end_offset: 15,
+                }
+            ]
+        );
+    }
+
+    fn position(line: u32, column: usize, offset: usize) -> Position {
+        Position {
+            line,
+            column,
+            offset,
+        }
+    }
+
+    #[test]
+    fn test_parse_error_unclosed_block_tag() {
+        assert_eq!(
+            parse("<body>\n  <mt:Entries><$mt:EntryTitle$>\n</body>"),
+            Err(ParseError::UnclosedBlockTag {
+                name: "Entries".to_string(),
+                position: position(2, 3, 9),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_error_mismatched_closing_tag() {
+        assert_eq!(
+            parse("<mt:Entries><mt:If></mt:Entries></mt:If>"),
+            Err(ParseError::MismatchedClosingTag {
+                expected: "If".to_string(),
+                found: "Entries".to_string(),
+                position: position(1, 20, 19),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_error_stray_closing_tag() {
+        assert_eq!(
+            parse("<mt:Entries></mt:Entries></mt:Entries>"),
+            Err(ParseError::StrayClosingTag {
+                name: "Entries".to_string(),
+                position: position(1, 26, 25),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_error_unterminated_tag() {
+        assert_eq!(
+            parse(r#"<p><$mt:Var name="foo""#),
+            Err(ParseError::UnterminatedTag {
+                name: "Var".to_string(),
+                position: position(1, 4, 3),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_error_invalid_tag_name() {
+        assert_eq!(
+            parse("<mt:123>"),
+            Err(ParseError::InvalidTagName {
+                position: position(1, 1, 0),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_error_unterminated_attribute_quote() {
+        assert_eq!(
+            parse(r#"<$mt:Var name="foo$>"#),
+            Err(ParseError::UnterminatedAttributeQuote {
+                name: "name".to_string(),
+                position: position(1, 15, 14),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_error_missing_attribute_equals() {
+        assert_eq!(
+            parse(r#"<$mt:Var name"foo"$>"#),
+            Err(ParseError::MissingAttributeEquals {
+                name: "name".to_string(),
+                position: position(1, 10, 9),
+            })
+        );
+    }
+
+    fn recovered_errors(input: &str) -> (Vec<Node>, Vec<ParseError>) {
+        let (node, diagnostics) = parse_with_recovery(input);
+        let children = match node {
+            Root(RootNode { children }) => children,
+            _ => unreachable!(),
+        };
+        return (children, diagnostics.into_iter().map(|d| d.error).collect());
+    }
+
+    #[test]
+    fn test_parse_with_recovery_unclosed_block_tag() {
+        let (children, errors) = recovered_errors("<mt:Entries><$mt:EntryTitle$>");
+        assert_eq!(
+            errors,
+            vec![ParseError::UnclosedBlockTag {
+                name: "Entries".to_string(),
+                position: position(1, 1, 0),
+            }]
+        );
+        match &children[..] {
+            [BlockTag(BlockTagNode {
+                children,
+                body,
+                close_tag,
+                ..
+            })] => {
+                assert_eq!(close_tag, &None);
+                assert_eq!(body.end, position(1, 30, 29));
+                assert_eq!(
+                    children.last(),
+                    Some(&Missing(MissingNode {
+                        name: "Entries".to_string(),
+                        line: 1,
+                        column: 30,
+                        offset: 29,
+                        end_line: 1,
+                        end_column: 30,
+                        end_offset: 29,
+                    }))
+                );
+            }
+            _ => panic!("unexpected children: {:?}", children),
+        }
+    }
+
+    #[test]
+    fn test_parse_with_recovery_misplaced_else() {
+        let input = r#"<mt:Else>a<mt:Entries>b<mt:ElseIf name="x"></mt:Entries><mt:If name="y"><mt:Else></mt:If><mt:IfMyPlugin><mt:Else></mt:IfMyPlugin><mt:iframe><mt:Else></mt:iframe><mt:Entries><mt:EntriesHeader>a<mt:Else>b</mt:EntriesHeader></mt:Entries>"#;
+        let (_, diagnostics) = parse_with_recovery(input);
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.severity == Severity::Warning));
+        let (children, errors) = recovered_errors(input);
+        assert_eq!(
+            errors,
+            vec![
+                ParseError::MisplacedElse {
+                    name: "Else".to_string(),
+                    position: position(1, 1, 0),
+                },
+                ParseError::MisplacedElse {
+                    name: "ElseIf".to_string(),
+                    position: position(1, 24, 23),
+                },
+                ParseError::MisplacedElse {
+                    name: "Else".to_string(),
+                    position: position(1, 141, 140),
+                },
+            ]
+        );
+        let conditional = children
+            .iter()
+            .filter_map(|child| match child {
+                BlockTag(tag) => Some((tag.name.as_str(), tag.conditional)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            conditional,
+            vec![
+                ("Entries", false),
+                ("If", true),
+                ("IfMyPlugin", true),
+                ("iframe", false),
+                ("Entries", false)
+            ]
+        );
+        match children.last() {
+            Some(BlockTag(BlockTagNode { children, .. })) => {
+                assert!(matches!(&children[0], BlockTag(tag) if tag.conditional));
+            }
+            res => panic!("unexpected child: {:?}", res),
+        }
+        assert!(parse(input).is_ok());
+    }
+
+    #[test]
+    fn test_parse_with_recovery_closes_ancestor() {
+        let (children, errors) = recovered_errors("<mt:Entries><mt:Foo>x</mt:Entries>y");
+        assert_eq!(
+            errors,
+            vec![ParseError::UnclosedBlockTag {
+                name: "Foo".to_string(),
+                position: position(1, 13, 12),
+            }]
+        );
+        match &children[..] {
+            [BlockTag(entries), Text(TextNode { value, .. })] => {
+                assert_eq!(entries.name, "Entries");
+                assert_eq!(value, "y");
+                match &entries.children[..] {
+                    [BlockTag(foo)] => {
+                        assert_eq!(foo.name, "Foo");
+                        assert!(matches!(foo.children[..], [Text(_), Missing(_)]));
+                    }
+                    _ => panic!("unexpected children: {:?}", entries.children),
+                }
+            }
+            _ => panic!("unexpected children: {:?}", children),
+        }
+    }
+
+    #[test]
+    fn test_parse_with_recovery_stray_closing_tag() {
+        let (children, errors) = recovered_errors("a</mt:Entries>b");
+        assert_eq!(
+            errors,
+            vec![ParseError::StrayClosingTag {
+                name: "Entries".to_string(),
+                position: position(1, 2, 1),
+            }]
+        );
+        assert_eq!(
+            children[1],
+            Error(ErrorNode {
+                value: "</mt:Entries>".to_string(),
+                line: 1,
+                column: 2,
+                offset: 1,
+                end_line: 1,
+                end_column: 15,
+                end_offset: 14,
+            })
+        );
+        assert_eq!(children.len(), 3);
+    }
+
+    #[test]
+    fn test_parse_with_recovery_mismatched_closing_tag() {
+        let input = "<mt:Entries></mt:Entrie>";
+        let error = ParseError::MismatchedClosingTag {
+            expected: "Entries".to_string(),
+            found: "Entrie".to_string(),
+            position: position(1, 13, 12),
+        };
+        assert_eq!(parse(input), Err(error.clone()));
+        let (children, errors) = recovered_errors(input);
+        assert_eq!(errors[0], error);
+        match &children[..] {
+            [BlockTag(BlockTagNode { children, .. })] => {
+                assert!(matches!(&children[0], Error(node) if node.value == "</mt:Entrie>"));
+            }
+            _ => panic!("unexpected children: {:?}", children),
+        }
+    }
+
+    #[test]
+    fn test_parse_with_recovery_broken_attribute() {
+        let (children, errors) = recovered_errors(r#"<$mt:Var foo"x" bar="1"$>"#);
+        assert_eq!(
+            errors,
+            vec![ParseError::MissingAttributeEquals {
+                name: "foo".to_string(),
+                position: position(1, 10, 9),
+            }]
+        );
+        match &children[..] {
+            [FunctionTag(FunctionTagNode {
+                name,
+                attributes,
+                tail,
+                ..
+            })] => {
+                assert_eq!(name, "Var");
+                assert!(attributes.is_empty());
+                assert_eq!(tail, r#" foo"x" bar="1"$"#);
+            }
+            _ => panic!("unexpected children: {:?}", children),
+        }
+
+        for input in [r#"<$mt:Var foo"x" bar="1"$>"#, r#"<mt:Var name="x>"#] {
+            let (node, _) = parse_with_recovery(input);
+            let serialized = crate::serializer::serialize(
+                node,
+                Some(crate::serializer::Options {
+                    lossless: true,
+                    ..Default::default()
+                }),
+            );
+            assert_eq!(serialized, input);
+        }
+    }
+
+    #[test]
+    fn test_parse_with_recovery_broken_tag() {
+        let (children, errors) = recovered_errors("<mt:>text");
+        assert_eq!(
+            errors,
+            vec![ParseError::InvalidTagName {
+                position: position(1, 1, 0),
+            }]
+        );
+        assert_eq!(
+            children,
+            vec![
+                Error(ErrorNode {
+                    value: "<mt:>".to_string(),
+                    line: 1,
+                    column: 1,
+                    offset: 0,
+                    end_line: 1,
+                    end_column: 6,
+                    end_offset: 5,
+                }),
+                Text(TextNode {
+                    value: "text".to_string(),
+                    line: 1,
+                    column: 6,
+                    offset: 5,
+                    end_line: 1,
+                    end_column: 10,
+                    end_offset: 9,
+                    synthetic: false,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parser_config_function_tag() {
+        let input = "<mt:Entries><mt:MyPluginTag></mt:Entries>";
+        assert_eq!(
+            parse(input),
+            Err(ParseError::MismatchedClosingTag {
+                expected: "MyPluginTag".to_string(),
+                found: "Entries".to_string(),
+                position: position(1, 29, 28),
+            })
+        );
+
+        let parser = ParserConfig::default().function_tag("myplugintag").build();
+        match parser.parse(input) {
+            Ok(Root(RootNode { children })) => match &children[..] {
+                [BlockTag(BlockTagNode { children, .. })] => {
+                    assert!(matches!(children[..], [FunctionTag(_)]));
+                }
+                _ => panic!("unexpected children: {:?}", children),
+            },
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn test_parser_config_is_scoped() {
+        let config = ParserConfig::empty()
+            .function_tag("Foo")
+            .conditional_tag("IfFoo");
+        assert!(config.is_function_tag("foo"));
+        assert!(config.is_block_tag("iffoo"));
+        assert!(config.is_conditional_tag("IFFOO"));
+        assert!(!config.is_function_tag("EntryTitle"));
+        assert!(!ParserConfig::default().is_function_tag("Foo"));
+
+        let config = config.block_tag("Foo");
+        assert!(!config.is_function_tag("Foo"));
+        assert!(config.is_block_tag("Foo"));
+
+        assert!(config.is_conditional_tag("IfBar"));
+        assert!(!config.is_conditional_tag("Bar"));
+        assert!(!config.is_conditional_tag("iframe"));
+        assert!(!config.block_tag("IfBar").is_conditional_tag("IfBar"));
+    }
+
+    #[test]
+    fn test_parser_config_plugin_tags() {
+        let tags = PluginTags {
+            function_tags: vec!["MyPluginTag".to_string()],
+            block_tags: vec!["MyPluginBlock".to_string()],
+            conditional_tags: vec!["IfMyPlugin".to_string()],
+            modifiers: vec![],
+        };
+        let config = ParserConfig::default().plugin_tags(&tags);
+        assert!(config.is_function_tag("myplugintag"));
+        assert!(config.is_block_tag("MyPluginBlock"));
+        assert!(config.is_conditional_tag("IfMyPlugin"));
+        assert!(config
+            .build()
+            .parse("<mt:IfMyPlugin><mt:MyPluginTag><mt:Else>-</mt:IfMyPlugin>")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_parser_config_catalog() {
+        let config = ParserConfig::default();
+        assert!(config.is_block_tag("Entries"));
+        assert!(config.is_conditional_tag("IfNonEmpty"));
+        assert!(!config.is_block_tag("EntryTitle"));
+        assert!(config
+            .build()
+            .parse(r#"<mt:If name="a">a<mt:ElseIf name="b">b<mt:Else>c</mt:If>"#)
+            .is_ok());
+
+        let config = ParserConfig::empty().tag_catalog(&CATALOG);
+        assert!(config.is_function_tag("EntryTitle"));
+        assert!(config.is_conditional_tag("If"));
+    }
+
+    #[test]
+    fn test_take_until_tag() {
+        let cases = [
+            ("日本語<mt:Var>", "日本語"),
+            ("日本語<$mt:Var$>", "日本語"),
+            ("日本語</mt:If>", "日本語"),
+            ("<p>日本語</p><MT:Var>", "<p>日本語</p>"),
+            ("a < b <$b", "a < b <$b"),
+            ("<", "<"),
+            ("<m", "<m"),
+            ("<$", "<$"),
+            ("</m", "</m"),
+            ("日本語<", "日本語<"),
+            ("日本語<ｍｔ", "日本語<ｍｔ"),
+            ("<日本語", "<日本語"),
+            ("", ""),
+        ];
+        for (input, expected) in cases {
+            let (rest, text) = take_until_tag(Span::new(input), false).unwrap();
+            assert_eq!(*text.fragment(), expected, "input: {:?}", input);
+            assert_eq!(rest.location_offset(), expected.len(), "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_multibyte_and_truncated_input() {
+        let inputs = [
+            "日本語<mt:Var name=\"タイトル\">です",
+            "<mt:Entries>記事：<$mt:EntryTitle$>。</mt:Entries>",
+            "日本語<",
+            "日本語<m",
+            "日本語<mt",
+            "日本語<mt:",
+            "日本語<$mt:Var",
+            "日本語<mt:Var name=\"値",
+            "日本語<mt:Var name=\"値\"",
+            "<mt:Entries>日本語</mt:Entri",
+            "<mt:Entries>日本語</",
+            "<mt:If name=\"a\">はい<mt:Else>いいえ",
+            "<mt:日本語>",
+            "🍣<mt:Var name=\"🍣\">🍣",
+        ];
+        for input in inputs {
+            let _ = parse(input);
+            let (root, _) = parse_with_recovery(input);
+            let serialized = crate::serializer::serialize(
+                root,
+                Some(crate::serializer::Options {
+                    lossless: true,
+                    ..Default::default()
+                }),
+            );
+            assert_eq!(serialized, input);
+        }
+    }
+
+    #[test]
+    fn test_parse_multibyte_position() {
+        let input = "日本語<mt:Var name=\"a\">";
+        match parse(input) {
+            Ok(Root(RootNode { children })) => match &children[..] {
+                [Text(text), FunctionTag(tag)] => {
+                    assert_eq!(text.value, "日本語");
+                    assert_eq!((tag.line, tag.column, tag.offset), (1, 4, 9));
+                }
+                _ => panic!("unexpected children: {:?}", children),
+            },
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn test_parse_ignore() {
+        let input = r#"<mt:Ignore note="x"><mt:Entries><mt:Ignore>a</mt:Ignore></mt:Foo><$mt:Var</mt:Ignore>"#;
+        match parse(input) {
+            Ok(Root(RootNode { children })) => match &children[..] {
+                [Ignore(IgnoreNode {
+                    name,
+                    attributes,
+                    value,
+                    body,
+                    close_tag,
+                    ..
+                })] => {
+                    assert_eq!(name, "Ignore");
+                    assert_eq!(attributes[0].name, "note");
+                    assert_eq!(
+                        value,
+                        "<mt:Entries><mt:Ignore>a</mt:Ignore></mt:Foo><$mt:Var"
+                    );
+                    assert_eq!(body.start.offset, 20);
+                    assert_eq!(close_tag.unwrap().start.offset, 73);
                 }
+                _ => panic!("unexpected children: {:?}", children),
+            },
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        assert!(matches!(
+            parse("<mt:Ignore /><$mt:Ignore$>"),
+            Ok(Root(RootNode { children })) if matches!(children[..], [FunctionTag(_), FunctionTag(_)])
+        ));
+    }
+
+    #[test]
+    fn test_parse_unclosed_ignore() {
+        let input = "<mt:If><mt:Ignore></mt:If>";
+        assert_eq!(
+            parse(input),
+            Err(ParseError::UnclosedBlockTag {
+                name: "Ignore".to_string(),
+                position: position(1, 8, 7),
+            })
+        );
+
+        let (children, errors) = recovered_errors(input);
+        assert_eq!(errors.len(), 2);
+        match &children[..] {
+            [BlockTag(BlockTagNode { children, .. })] => match &children[..] {
+                [Ignore(IgnoreNode {
+                    value, close_tag, ..
+                }), Missing(_)] => {
+                    assert_eq!(value, "</mt:If>");
+                    assert_eq!(*close_tag, None);
+                }
+                _ => panic!("unexpected children: {:?}", children),
+            },
+            _ => panic!("unexpected children: {:?}", children),
+        }
+    }
+
+    #[test]
+    fn test_parse_html_comments() {
+        let input = "<!-- <mt:If> -->a<!-- <mt:EntryTitle> --><mt:Var name=\"x\"><!-- unterminated";
+        assert!(parse(input).is_err());
+
+        let parser = ParserConfig::default().html_comments(true).build();
+        match parser.parse(input) {
+            Ok(Root(RootNode { children })) => match &children[..] {
+                [Comment(first), Text(_), Comment(second), FunctionTag(_), Text(text)] => {
+                    assert_eq!(first.value, " <mt:If> ");
+                    assert_eq!((first.offset, first.end_offset), (0, 16));
+                    assert_eq!(second.value, " <mt:EntryTitle> ");
+                    assert_eq!(text.value, "<!-- unterminated");
+                }
+                _ => panic!("unexpected children: {:?}", children),
+            },
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn test_parse_unquoted_and_valueless_attributes() {
+        let attributes = |input| match parse(input) {
+            Ok(Root(RootNode { mut children })) => match children.remove(0) {
+                FunctionTag(FunctionTagNode { attributes, .. })
+                | BlockTag(BlockTagNode { attributes, .. }) => attributes,
+                node => panic!("unexpected node: {:?}", node),
+            },
+            res => panic!("unexpected result: {:?}", res),
+        };
+        let summary = |attributes: Vec<Attribute>| {
+            attributes
+                .into_iter()
+                .map(|attr| {
+                    let values: Vec<String> = attr.values.into_iter().map(|v| v.value).collect();
+                    (attr.name, values, attr.syntax)
+                })
+                .collect::<Vec<_>>()
+        };
+        let unquoted = |name: &str, value: &str| {
+            (
+                name.to_string(),
+                vec![value.to_string()],
+                AttributeSyntax::Unquoted,
+            )
+        };
+        let valueless = |name: &str| (name.to_string(), vec![], AttributeSyntax::Valueless);
+
+        assert_eq!(
+            summary(attributes("<$mt:Var name=foo$>")),
+            vec![unquoted("name", "foo")]
+        );
+        assert_eq!(
+            summary(attributes("<mt:Var name=foo/>")),
+            vec![unquoted("name", "foo")]
+        );
+        assert_eq!(
+            summary(attributes("<mt:Entries lastn=5>x</mt:Entries>")),
+            vec![unquoted("lastn", "5")]
+        );
+        assert_eq!(
+            summary(attributes("<$mt:Var foo encode_html$>")),
+            vec![valueless("foo"), valueless("encode_html")]
+        );
+        assert_eq!(
+            summary(attributes(r#"<mt:Var name=a/b replace="x",y trim>"#)),
+            vec![
+                unquoted("name", "a/b"),
+                (
+                    "replace".to_string(),
+                    vec!["x".to_string(), "y".to_string()],
+                    AttributeSyntax::Unquoted
+                ),
+                valueless("trim"),
             ]
         );
+
+        let attribute = attributes("<$mt:Var  name=foo$>").remove(0);
+        assert_eq!((attribute.offset, attribute.end_offset), (10, 18));
+        assert_eq!(
+            (attribute.values[0].offset, attribute.values[0].end_offset),
+            (15, 18)
+        );
+        let attribute = attributes("<$mt:Var foo$>").remove(0);
+        assert_eq!((attribute.offset, attribute.end_offset), (9, 12));
+    }
+
+    #[test]
+    fn test_parse_attribute_with_tags() {
+        let input = r#"<$mt:Var name="title" value="<$mt:EntryTitle encode_html="1"$>!"$>"#;
+        let values = |node| match node {
+            Ok(Root(RootNode { mut children })) => match children.remove(0) {
+                FunctionTag(FunctionTagNode { mut attributes, .. }) => attributes.remove(1).values,
+                node => panic!("unexpected node: {:?}", node),
+            },
+            res => panic!("unexpected result: {:?}", res),
+        };
+
+        let mut default_values = values(parse(input));
+        assert_eq!(
+            default_values[0].value,
+            r#"<$mt:EntryTitle encode_html="1"$>!"#
+        );
+        assert_eq!(default_values.remove(0).children, None);
+
+        let parser = ParserConfig::default().attribute_tags(true).build();
+        let mut values = values(parser.parse(input));
+        match values.remove(0).children.unwrap().as_slice() {
+            [FunctionTag(tag), Text(text)] => {
+                assert_eq!(tag.name, "EntryTitle");
+                assert_eq!(tag.attributes[0].values[0].value, "1");
+                assert_eq!((tag.offset, tag.end_offset), (29, 62));
+                assert_eq!(text.value, "!");
+            }
+            children => panic!("unexpected children: {:?}", children),
+        }
+
+        assert_eq!(
+            parser.parse(r#"<$mt:Var name="x" value="<mt:If>"$>"#),
+            Err(ParseError::UnclosedBlockTag {
+                name: "If".to_string(),
+                position: position(1, 26, 25),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_attribute_quoted_value() {
+        let value = |input| match parse_attribute(
+            &mut State::new(&ParserConfig::default(), false),
+            Span::new(input),
+        ) {
+            Ok((_, Some(mut attribute))) => attribute.values.remove(0).value,
+            res => panic!("unexpected result: {:?}", res),
+        };
+        assert_eq!(
+            value(r#"a="<$mt:Var name="x"$>""#),
+            r#"<$mt:Var name="x"$>"#
+        );
+        assert_eq!(value(r#"a="1 < 2" b=">""#), "1 < 2");
+        assert_eq!(value(r#"a="\d+""#), r#"\d+"#);
+        assert_eq!(value(r#"a="""#), "");
+        // Skipping `<y" b="z">` as a tag would leave the value unterminated.
+        assert_eq!(value(r#"a="x<y" b="z">"#), "x<y");
     }
 }
@@ -0,0 +1,212 @@
+extern crate serde_yaml;
+
+use serde_yaml::Value;
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Tags declared under `tags:` in a Movable Type plugin's `config.yaml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PluginTags {
+    pub function_tags: Vec<String>,
+    /// Block tags, without conditional tags.
+    pub block_tags: Vec<String>,
+    /// Block tags declared with the `?` suffix, stored without it.
+    pub conditional_tags: Vec<String>,
+    pub modifiers: Vec<String>,
+}
+
+impl PluginTags {
+    /// Add the tags of `other` to `self`.
+    pub fn merge(&mut self, other: PluginTags) {
+        self.function_tags.extend(other.function_tags);
+        self.block_tags.extend(other.block_tags);
+        self.conditional_tags.extend(other.conditional_tags);
+        self.modifiers.extend(other.modifiers);
+    }
+}
+
+#[derive(Debug)]
+pub enum PluginError {
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+    Yaml {
+        path: PathBuf,
+        error: serde_yaml::Error,
+    },
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PluginError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            PluginError::Yaml { path, error } => write!(f, "{}: {}", path.display(), error),
+        }
+    }
+}
+
+impl std::error::Error for PluginError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PluginError::Io { error, .. } => Some(error),
+            PluginError::Yaml { error, .. } => Some(error),
+        }
+    }
+}
+
+fn tag_names(tags: &Value, kind: &str) -> Vec<String> {
+    return match tags.get(kind) {
+        Some(Value::Mapping(mapping)) => mapping
+            .keys()
+            .filter_map(|key| key.as_str())
+            .map(|key| key.to_string())
+            .collect(),
+        _ => vec![],
+    };
+}
+
+/// Read the tags declared in the contents of a plugin `config.yaml`.
+///
+/// # Examples
+///
+/// ```
+/// use mtml_parser::plugin::parse_plugin_config;
+///
+/// let tags = parse_plugin_config(r#"
+/// name: MyPlugin
+/// tags:
+///   function:
+///     MyPluginTag: $MyPlugin::MyPlugin::Tags::hdlr_tag
+///   block:
+///     MyPluginBlock: $MyPlugin::MyPlugin::Tags::hdlr_block
+///     IfMyPlugin?: $MyPlugin::MyPlugin::Tags::hdlr_if
+/// "#).unwrap();
+/// assert_eq!(tags.function_tags, vec!["MyPluginTag"]);
+/// assert_eq!(tags.conditional_tags, vec!["IfMyPlugin"]);
+/// ```
+pub fn parse_plugin_config(yaml: &str) -> Result<PluginTags, serde_yaml::Error> {
+    let config: Value = serde_yaml::from_str(yaml)?;
+    let tags = match config.get("tags") {
+        // `tags` may also be a code reference such as `$MyPlugin::load_tags`.
+        Some(tags @ Value::Mapping(_)) => tags,
+        _ => return Ok(Default::default()),
+    };
+
+    let mut block_tags = vec![];
+    let mut conditional_tags = vec![];
+    for name in tag_names(tags, "block") {
+        match name.strip_suffix('?') {
+            Some(name) => conditional_tags.push(name.to_string()),
+            None => block_tags.push(name),
+        }
+    }
+
+    return Ok(PluginTags {
+        function_tags: tag_names(tags, "function"),
+        block_tags,
+        conditional_tags,
+        modifiers: tag_names(tags, "modifier"),
+    });
+}
+
+/// Read the tags declared in a plugin `config.yaml` file.
+pub fn load_plugin_config<P: AsRef<Path>>(path: P) -> Result<PluginTags, PluginError> {
+    let path = path.as_ref();
+    let yaml = fs::read_to_string(path).map_err(|error| PluginError::Io {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    return parse_plugin_config(&yaml).map_err(|error| PluginError::Yaml {
+        path: path.to_path_buf(),
+        error,
+    });
+}
+
+/// Read the tags of every plugin in a plugin directory such as `mt/plugins`.
+///
+/// Each subdirectory with a `config.yaml` is treated as a plugin. Plugins are
+/// read in name order.
+pub fn load_plugin_dir<P: AsRef<Path>>(dir: P) -> Result<PluginTags, PluginError> {
+    let dir = dir.as_ref();
+    let io_error = |error| PluginError::Io {
+        path: dir.to_path_buf(),
+        error,
+    };
+
+    let mut configs = vec![];
+    for entry in fs::read_dir(dir).map_err(io_error)? {
+        let config = entry.map_err(io_error)?.path().join("config.yaml");
+        if config.is_file() {
+            configs.push(config);
+        }
+    }
+    configs.sort();
+
+    let mut tags = PluginTags::default();
+    for config in configs {
+        tags.merge(load_plugin_config(config)?);
+    }
+
+    return Ok(tags);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+id: MyPlugin
+name: MyPlugin
+tags:
+  help_url: https://example.com/tags/%t
+  function:
+    MyPluginTag: $MyPlugin::MyPlugin::Tags::hdlr_tag
+  block:
+    MyPluginBlock:
+      handler: $MyPlugin::MyPlugin::Tags::hdlr_block
+    IfMyPlugin?: $MyPlugin::MyPlugin::Tags::hdlr_if
+  modifier:
+    my_plugin_filter: $MyPlugin::MyPlugin::Tags::hdlr_filter
+"#;
+
+    #[test]
+    fn test_parse_plugin_config() {
+        assert_eq!(
+            parse_plugin_config(CONFIG).unwrap(),
+            PluginTags {
+                function_tags: vec!["MyPluginTag".to_string()],
+                block_tags: vec!["MyPluginBlock".to_string()],
+                conditional_tags: vec!["IfMyPlugin".to_string()],
+                modifiers: vec!["my_plugin_filter".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_plugin_config_without_tags() {
+        assert_eq!(
+            parse_plugin_config("name: NoTags\n").unwrap(),
+            PluginTags::default()
+        );
+        assert_eq!(
+            parse_plugin_config("tags: $MyPlugin::MyPlugin::load_tags\n").unwrap(),
+            PluginTags::default()
+        );
+    }
+
+    #[test]
+    fn test_load_plugin_dir() {
+        let dir = std::env::temp_dir().join(format!("mtml-parser-plugins-{}", std::process::id()));
+        let plugin = dir.join("MyPlugin");
+        fs::create_dir_all(&plugin).unwrap();
+        fs::create_dir_all(dir.join("NotAPlugin")).unwrap();
+        fs::write(plugin.join("config.yaml"), CONFIG).unwrap();
+
+        let tags = load_plugin_dir(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(tags.unwrap().function_tags, vec!["MyPluginTag"]);
+    }
+}
@@ -0,0 +1,419 @@
+//! CSS-like selectors over the AST.
+//!
+//! A selector is a list of tag names separated by ` ` (descendant) or `>`
+//! (child), each optionally followed by attribute filters:
+//!
+//! - `Var` matches `<mt:Var>`, `<MTVar>` and `<$mt:var$>`; `mt:Var` is the same selector.
+//! - `*` matches any tag.
+//! - `[setvar]` requires the attribute, `[name=title]` or `[name="title"]` requires
+//!   one of its values to be `title`.
+//! - `A, B` matches either selector.
+//!
+//! Tag and attribute names are matched case-insensitively.
+//!
+//! # Examples
+//!
+//! ```
+//! use mtml_parser::{parse, query::query};
+//!
+//! let node = parse(r#"<mt:Entries><$mt:Var name="x" setvar="y"$></mt:Entries><$mt:Var name="z"$>"#).unwrap();
+//! let matches = query(&node, "Entries > Var[setvar]").unwrap();
+//! assert_eq!(matches.len(), 1);
+//! assert_eq!(matches[0].path, vec![0, 0]);
+//! ```
+
+use std::{fmt, str::FromStr};
+
+use super::ast::{Node, SourceRange};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectorError {
+    /// The selector is empty, or a combinator is not followed by a tag.
+    UnexpectedEnd,
+    /// A character that is not valid at `offset`.
+    UnexpectedChar { ch: char, offset: usize },
+}
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SelectorError::UnexpectedEnd => write!(f, "Unexpected end of selector"),
+            SelectorError::UnexpectedChar { ch, offset } => {
+                write!(f, "Unexpected '{}' at offset {} of selector", ch, offset)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SelectorError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AttributeFilter {
+    name: String,
+    value: Option<String>,
+}
+
+/// A tag name with attribute filters, e.g. `Var[setvar]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Compound {
+    /// Lowercase name without prefix, or `None` for `*`.
+    name: Option<String>,
+    attributes: Vec<AttributeFilter>,
+}
+
+impl Compound {
+    fn matches(&self, node: &Node) -> bool {
+        let (name, attributes) = match (node.tag_name(), node.attributes()) {
+            (Some(name), Some(attributes)) => (name, attributes),
+            _ => return false,
+        };
+        if let Some(expected) = &self.name {
+            if !name.eq_ignore_ascii_case(expected) {
+                return false;
+            }
+        }
+        return self.attributes.iter().all(|filter| {
+            attributes.iter().any(|attr| {
+                attr.name.eq_ignore_ascii_case(&filter.name)
+                    && match &filter.value {
+                        Some(value) => attr.values.iter().any(|v| &v.value == value),
+                        None => true,
+                    }
+            })
+        });
+    }
+}
+
+/// Parsed selector. See the module documentation for the syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selector {
+    /// Alternatives separated by `,`, each a list of compounds with the
+    /// combinator that precedes them.
+    alternatives: Vec<Vec<(Combinator, Compound)>>,
+}
+
+/// A node matched by a selector.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Match<'a> {
+    pub node: &'a Node,
+    /// Child indices from the queried node down to `node`.
+    pub path: Vec<usize>,
+    pub range: SourceRange,
+}
+
+struct SelectorParser<'s> {
+    input: &'s str,
+    offset: usize,
+}
+
+impl<'s> SelectorParser<'s> {
+    fn peek(&self) -> Option<char> {
+        return self.input[self.offset..].chars().next();
+    }
+
+    fn skip_whitespace(&mut self) -> bool {
+        let rest = &self.input[self.offset..];
+        let trimmed = rest.trim_start();
+        self.offset += rest.len() - trimmed.len();
+        return rest.len() != trimmed.len();
+    }
+
+    fn unexpected(&self) -> SelectorError {
+        return match self.peek() {
+            Some(ch) => SelectorError::UnexpectedChar {
+                ch,
+                offset: self.offset,
+            },
+            None => SelectorError::UnexpectedEnd,
+        };
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'s str {
+        let rest = &self.input[self.offset..];
+        let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.offset += len;
+        return &rest[..len];
+    }
+
+    fn name(&mut self) -> Result<&'s str, SelectorError> {
+        let name = self.take_while(|c| c.is_alphanumeric() || c == '_' || c == ':');
+        if name.is_empty() {
+            return Err(self.unexpected());
+        }
+        return Ok(name);
+    }
+
+    fn expect(&mut self, ch: char) -> Result<(), SelectorError> {
+        if self.peek() != Some(ch) {
+            return Err(self.unexpected());
+        }
+        self.offset += ch.len_utf8();
+        return Ok(());
+    }
+
+    fn value(&mut self) -> Result<String, SelectorError> {
+        return match self.peek() {
+            Some(quote @ ('"' | '\'')) => {
+                self.offset += 1;
+                let value = self.take_while(|c| c != quote);
+                self.expect(quote)?;
+                Ok(value.to_string())
+            }
+            _ => Ok(self.name()?.to_string()),
+        };
+    }
+
+    fn compound(&mut self) -> Result<Compound, SelectorError> {
+        let name = if self.peek() == Some('*') {
+            self.offset += 1;
+            None
+        } else {
+            Some(strip_prefix(self.name()?).to_lowercase())
+        };
+
+        let mut attributes = vec![];
+        while self.peek() == Some('[') {
+            self.offset += 1;
+            self.skip_whitespace();
+            let name = self.name()?.to_string();
+            self.skip_whitespace();
+            let value = if self.peek() == Some('=') {
+                self.offset += 1;
+                self.skip_whitespace();
+                Some(self.value()?)
+            } else {
+                None
+            };
+            self.skip_whitespace();
+            self.expect(']')?;
+            attributes.push(AttributeFilter { name, value });
+        }
+
+        return Ok(Compound { name, attributes });
+    }
+
+    fn parse(&mut self) -> Result<Selector, SelectorError> {
+        let mut alternatives = vec![];
+        let mut compounds = vec![];
+        let mut combinator = Combinator::Descendant;
+        self.skip_whitespace();
+        loop {
+            compounds.push((combinator, self.compound()?));
+            let whitespace = self.skip_whitespace();
+            combinator = match self.peek() {
+                None => break,
+                Some(',') => {
+                    self.offset += 1;
+                    self.skip_whitespace();
+                    alternatives.push(std::mem::take(&mut compounds));
+                    Combinator::Descendant
+                }
+                Some('>') => {
+                    self.offset += 1;
+                    self.skip_whitespace();
+                    Combinator::Child
+                }
+                Some(_) if whitespace => Combinator::Descendant,
+                Some(_) => return Err(self.unexpected()),
+            };
+        }
+        alternatives.push(compounds);
+
+        return Ok(Selector { alternatives });
+    }
+}
+
+/// Strip an `mt:` or `MT` prefix, e.g. `mt:Var` or `MTVar` to `Var`.
+fn strip_prefix(name: &str) -> &str {
+    let has_prefix = |prefix: &str| {
+        name.len() > prefix.len()
+            && name
+                .get(..prefix.len())
+                .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+    };
+    if has_prefix("mt:") {
+        return &name[3..];
+    }
+    if has_prefix("mt") && name[2..].starts_with(|c: char| c.is_ascii_uppercase()) {
+        return &name[2..];
+    }
+    return name;
+}
+
+impl FromStr for Selector {
+    type Err = SelectorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return Selector::parse(s);
+    }
+}
+
+impl Selector {
+    pub fn parse(selector: &str) -> Result<Selector, SelectorError> {
+        return SelectorParser {
+            input: selector,
+            offset: 0,
+        }
+        .parse();
+    }
+
+    /// Return whether `node`, whose enclosing tags are `ancestors` (outermost
+    /// first), matches the selector.
+    pub fn matches(&self, node: &Node, ancestors: &[&Node]) -> bool {
+        return self.alternatives.iter().any(|compounds| {
+            let (_, last) = compounds.last().unwrap();
+            last.matches(node) && matches_ancestors(compounds, compounds.len() - 1, ancestors)
+        });
+    }
+
+    /// Return the descendants of `node` matching the selector, in document order.
+    pub fn select<'a>(&self, node: &'a Node) -> Vec<Match<'a>> {
+        let mut matches = vec![];
+        let mut ancestors = vec![];
+        let mut path = vec![];
+        self.select_children(node, &mut ancestors, &mut path, &mut matches);
+        return matches;
+    }
+
+    fn select_children<'a>(
+        &self,
+        node: &'a Node,
+        ancestors: &mut Vec<&'a Node>,
+        path: &mut Vec<usize>,
+        matches: &mut Vec<Match<'a>>,
+    ) {
+        let children = match node.children() {
+            Some(children) => children,
+            None => return,
+        };
+        for (index, child) in children.iter().enumerate() {
+            path.push(index);
+            // A `Root` spliced into a tree has no range and is never matched.
+            if let (Some(range), true) = (child.range(), self.matches(child, ancestors)) {
+                matches.push(Match {
+                    node: child,
+                    path: path.clone(),
+                    range,
+                });
+            }
+            ancestors.push(child);
+            self.select_children(child, ancestors, path, matches);
+            ancestors.pop();
+            path.pop();
+        }
+    }
+}
+
+/// Check that the compounds before `index` match `ancestors`, given that
+/// `compounds[index]` matched the node whose ancestors they are.
+fn matches_ancestors(
+    compounds: &[(Combinator, Compound)],
+    index: usize,
+    ancestors: &[&Node],
+) -> bool {
+    if index == 0 {
+        return true;
+    }
+    let (combinator, _) = &compounds[index];
+    let (_, compound) = &compounds[index - 1];
+    return match combinator {
+        Combinator::Child => match ancestors.split_last() {
+            Some((parent, rest)) => {
+                compound.matches(parent) && matches_ancestors(compounds, index - 1, rest)
+            }
+            None => false,
+        },
+        Combinator::Descendant => (0..ancestors.len()).rev().any(|i| {
+            compound.matches(ancestors[i])
+                && matches_ancestors(compounds, index - 1, &ancestors[..i])
+        }),
+    };
+}
+
+/// Return the descendants of `node` matching `selector`.
+pub fn query<'a>(node: &'a Node, selector: &str) -> Result<Vec<Match<'a>>, SelectorError> {
+    return Ok(Selector::parse(selector)?.select(node));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parser::*;
+    use super::*;
+
+    const INPUT: &str = r#"<mt:Entries>
+  <$mt:Var name="a" setvar="x"$>
+  <mt:If name="a">
+    <MTVar name="b" SetVar="y">
+  </mt:If>
+</mt:Entries>
+<$mt:Var name="c" setvar="z"$>
+<mt:App:Form><mt:Var name="d"></mt:App:Form>"#;
+
+    fn names(selector: &str) -> Vec<String> {
+        let node = parse(INPUT).unwrap();
+        return query(&node, selector)
+            .unwrap()
+            .iter()
+            .map(|m| m.node.attributes().unwrap()[0].values[0].value.clone())
+            .collect();
+    }
+
+    #[test]
+    fn test_query() {
+        assert_eq!(names("Var"), vec!["a", "b", "c", "d"]);
+        assert_eq!(names("mt:var[setvar]"), vec!["a", "b", "c"]);
+        assert_eq!(names("MTEntries Var"), vec!["a", "b"]);
+        assert_eq!(names("Entries > Var[setvar]"), vec!["a"]);
+        assert_eq!(names("Entries > * > Var"), vec!["b"]);
+        assert_eq!(names("Var[name=b], Var[name='c']"), vec!["b", "c"]);
+        assert_eq!(names("App:Form > Var"), vec!["d"]);
+        assert!(names("日本語").is_empty());
+        assert_eq!(names("If[name=\"a\"]"), vec!["a"]);
+        assert!(names("Entries > Entries").is_empty());
+    }
+
+    #[test]
+    fn test_query_paths_and_ranges() {
+        let node = parse(INPUT).unwrap();
+        let matches = query(&node, "If Var").unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].path, vec![0, 3, 1]);
+        assert_eq!(matches[0].range.start.line, 4);
+        assert_eq!(matches[0].range.start.column, 5);
+    }
+
+    #[test]
+    fn test_query_nested_root() {
+        let node = Node::Root(crate::ast::RootNode {
+            children: vec![parse(INPUT).unwrap()],
+        });
+        let matches = query(&node, "*").unwrap();
+        assert!(matches.iter().all(|m| !matches!(m.node, Node::Root(_))));
+        let matches = query(&node, "Entries > Var").unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].path, vec![0, 0, 1]);
+    }
+
+    #[test]
+    fn test_selector_error() {
+        assert_eq!(Selector::parse(""), Err(SelectorError::UnexpectedEnd));
+        assert_eq!(
+            Selector::parse("Entries >"),
+            Err(SelectorError::UnexpectedEnd)
+        );
+        assert_eq!(
+            Selector::parse("Var[name"),
+            Err(SelectorError::UnexpectedEnd)
+        );
+        assert_eq!(
+            "Var.foo".parse::<Selector>(),
+            Err(SelectorError::UnexpectedChar { ch: '.', offset: 3 })
+        );
+    }
+}
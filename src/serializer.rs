@@ -1,7 +1,14 @@
 extern crate clap;
 use clap::{Parser, ValueEnum};
+use std::{
+    borrow::Cow,
+    fmt::{self, Write},
+    io,
+};
 
 use super::ast::{Node::*, *};
+use super::tag::CATALOG;
+use super::visit::{walk_block_tag, walk_function_tag, walk_ignore, Visit};
 
 #[derive(Debug, Clone, PartialEq, Eq, ValueEnum)]
 pub enum FunctionTagStyle {
@@ -9,6 +16,27 @@ pub enum FunctionTagStyle {
     SelfClosing,
 }
 
+/// Case of tag and attribute names in the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum NameCase {
+    /// As written in the source.
+    Preserve = 1,
+    /// As spelled in the tag catalogue, e.g. `EntryTitle`. Names not in the
+    /// catalogue are kept as written.
+    Canonical,
+    Lower,
+}
+
+/// Order of attributes in the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AttributeOrder {
+    /// As written in the source.
+    Preserve = 1,
+    /// Tag arguments such as `name` first, then modifiers such as
+    /// `encode_html`, each in source order. See `attribute_order_warnings`.
+    ArgumentsFirst,
+}
+
 #[derive(Debug, Clone, Parser)]
 #[command(author, version, about, long_about = None)]
 pub struct Options {
@@ -16,26 +44,563 @@ pub struct Options {
     pub prefix: String,
     #[arg(short, long, value_enum, default_value = "dollar")]
     pub function_tag_style: FunctionTagStyle,
+    /// Reproduce the source as written instead of normalizing prefixes, tag styles and quotes.
+    #[arg(short, long)]
+    pub lossless: bool,
+    /// Re-indent block tag bodies by nesting depth and wrap long attribute lists.
+    #[arg(long)]
+    pub pretty: bool,
+    /// Width of one indentation level in pretty mode.
+    #[arg(long, default_value_t = 2)]
+    pub indent_width: usize,
+    /// Indent with tabs instead of spaces in pretty mode.
+    #[arg(long)]
+    pub indent_tabs: bool,
+    /// Line width above which pretty mode puts each attribute of a tag on its own line.
+    #[arg(long, default_value_t = 100)]
+    pub max_width: usize,
+    /// Case of tag names.
+    #[arg(long, value_enum, default_value = "preserve")]
+    pub tag_case: NameCase,
+    /// Case of attribute names.
+    #[arg(long, value_enum, default_value = "preserve")]
+    pub attribute_case: NameCase,
+    /// Order of attributes.
+    #[arg(long, value_enum, default_value = "preserve")]
+    pub attribute_order: AttributeOrder,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        return Options {
+            prefix: "mt:".to_string(),
+            function_tag_style: FunctionTagStyle::Dollar,
+            lossless: false,
+            pretty: false,
+            indent_width: 2,
+            indent_tabs: false,
+            max_width: 100,
+            tag_case: NameCase::Preserve,
+            attribute_case: NameCase::Preserve,
+            attribute_order: AttributeOrder::Preserve,
+        };
+    }
+}
+
+fn quote_to_str(quote: QuoteStyle) -> &'static str {
+    return match quote {
+        QuoteStyle::Double => "\"",
+        QuoteStyle::Single => "'",
+        QuoteStyle::None => "",
+    };
+}
+
+/// Return whether `value` can be written unquoted and parsed back unchanged.
+fn is_safe_unquoted(value: &str) -> bool {
+    return !value.is_empty()
+        && !value.contains(|c: char| c.is_whitespace() || ['"', '\'', ',', '>'].contains(&c))
+        && !value.ends_with(['/', '$']);
+}
+
+/// Return whether `ch` occurs in `value` outside of tags such as `<$mt:Var name="x"$>`.
+fn contains_outside_tags(value: &str, ch: char) -> bool {
+    let mut rest = value;
+    while let Some(c) = rest.chars().next() {
+        if c == ch {
+            return true;
+        }
+        if c == '<' {
+            let tag = &rest[1..];
+            match tag.find('>') {
+                Some(len) if len > 0 && !tag.starts_with(char::is_whitespace) => {
+                    rest = &rest[len + 2..];
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    return false;
+}
+
+/// Quote `value` as `quote`, switching to the other quote character if the
+/// value contains the requested one. A value containing both is written in
+/// double quotes with `"` encoded as `&quot;`.
+fn quote_value(value: &str, quote: QuoteStyle) -> String {
+    let quote = match quote {
+        QuoteStyle::None if is_safe_unquoted(value) => return value.to_string(),
+        QuoteStyle::Single => '\'',
+        _ => '"',
+    };
+    let other = if quote == '"' { '\'' } else { '"' };
+
+    return if !contains_outside_tags(value, quote) {
+        format!("{}{}{}", quote, value, quote)
+    } else if !contains_outside_tags(value, other) {
+        format!("{}{}{}", other, value, other)
+    } else {
+        format!("\"{}\"", value.replace('"', "&quot;"))
+    };
+}
+
+/// Elements whose content is whitespace-sensitive, left as written in pretty mode.
+const RAW_ELEMENTS: [&str; 4] = ["pre", "script", "style", "textarea"];
+
+/// Return whether `input` starts with an opening or closing tag of the HTML element `name`.
+fn is_element(input: &str, name: &str, closing: bool) -> bool {
+    let rest = match input.strip_prefix(if closing { "</" } else { "<" }) {
+        Some(rest) => rest.as_bytes(),
+        None => return false,
+    };
+    return rest.len() > name.len()
+        && rest[..name.len()].eq_ignore_ascii_case(name.as_bytes())
+        && matches!(rest[name.len()], b'>' | b'/' | b' ' | b'\t' | b'\r' | b'\n');
 }
 
-fn attribute_to_string(attr: Attribute) -> String {
-    format!(
-        " {}={}",
-        attr.name,
-        attr.values
+/// Return the whitespace-sensitive element the output is inside after the
+/// tag at the start of `input`, given the element it was inside before.
+fn raw_element_after(input: &str, raw: Option<&'static str>) -> Option<&'static str> {
+    return match raw {
+        Some(name) if is_element(input, name, true) => None,
+        Some(name) => Some(name),
+        None => RAW_ELEMENTS
             .iter()
-            .map({
-                |AttributeValue { value, .. }| {
-                    if value.contains("\"") {
-                        format!("'{}'", value)
-                    } else {
-                        format!(r#""{}""#, value)
-                    }
+            .copied()
+            .find(|name| is_element(input, name, false)),
+    };
+}
+
+/// Output of the serializer, tracking the current line for pretty mode.
+struct Writer<'w, W: fmt::Write> {
+    out: &'w mut W,
+    pretty: bool,
+    tab_width: usize,
+    /// Leading whitespace of the current line.
+    indent: String,
+    column: usize,
+    at_line_start: bool,
+    /// Whitespace-sensitive element the output is inside, e.g. `pre`.
+    raw: Option<&'static str>,
+}
+
+impl<'w, W: fmt::Write> Writer<'w, W> {
+    fn new(out: &'w mut W, options: &Options) -> Self {
+        return Writer {
+            out,
+            pretty: options.pretty,
+            tab_width: options.indent_width.max(1),
+            indent: String::new(),
+            column: 0,
+            at_line_start: true,
+            raw: None,
+        };
+    }
+}
+
+impl<W: fmt::Write> fmt::Write for Writer<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.pretty {
+            for c in s.chars() {
+                if c == '\n' {
+                    self.indent.clear();
+                    self.column = 0;
+                    self.at_line_start = true;
+                    continue;
+                }
+                if self.at_line_start && (c == ' ' || c == '\t') {
+                    self.indent.push(c);
+                } else {
+                    self.at_line_start = false;
                 }
-            })
-            .collect::<Vec<String>>()
-            .join(",")
-    )
+                self.column += if c == '\t' { self.tab_width } else { 1 };
+            }
+        }
+        return self.out.write_str(s);
+    }
+}
+
+/// Indentation of a block tag body in pretty mode.
+struct BodyIndent {
+    /// Indentation of the line with the opening tag, used for the closing tag.
+    outer: String,
+    /// Indentation of the least indented lines of the body.
+    inner: String,
+    /// Source width of the least indented lines of the body.
+    min_width: usize,
+}
+
+fn indent_width(indent: &str, options: &Options) -> usize {
+    return indent
+        .chars()
+        .map(|c| {
+            if c == '\t' {
+                options.indent_width.max(1)
+            } else {
+                1
+            }
+        })
+        .sum();
+}
+
+fn indent_string(width: usize, options: &Options) -> String {
+    if options.indent_tabs {
+        let tab_width = options.indent_width.max(1);
+        return "\t".repeat(width / tab_width) + &" ".repeat(width % tab_width);
+    }
+    return " ".repeat(width);
+}
+
+/// Split a line into its leading whitespace and the rest.
+fn split_indent(line: &str) -> (&str, &str) {
+    let len = line.len() - line.trim_start_matches([' ', '\t']).len();
+    return line.split_at(len);
+}
+
+/// Return the source width of the least indented lines of a block body,
+/// skipping blank lines, the line of the closing tag and whitespace-sensitive
+/// elements.
+fn min_indent_width(
+    children: &[Node],
+    mut raw: Option<&'static str>,
+    options: &Options,
+) -> Option<usize> {
+    let mut min: Option<usize> = None;
+    for (i, child) in children.iter().enumerate() {
+        let text = match child {
+            Text(TextNode { value, .. }) => value,
+            _ => continue,
+        };
+        for (j, b) in text.bytes().enumerate() {
+            if b == b'<' {
+                raw = raw_element_after(&text[j..], raw);
+            }
+            if b != b'\n' || raw.is_some() {
+                continue;
+            }
+            let (indent, rest) = split_indent(&text[j + 1..]);
+            if rest.starts_with(['\r', '\n']) || (rest.is_empty() && i + 1 == children.len()) {
+                continue;
+            }
+            let width = indent_width(indent, options);
+            min = Some(min.map_or(width, |min| min.min(width)));
+        }
+    }
+    return min;
+}
+
+/// Write text, re-indenting its lines to `body` in pretty mode.
+fn write_text<W: fmt::Write>(
+    text: &str,
+    body: Option<&BodyIndent>,
+    is_last: bool,
+    options: &Options,
+    w: &mut Writer<W>,
+) -> fmt::Result {
+    if !w.pretty {
+        return w.write_str(text);
+    }
+
+    let bytes = text.as_bytes();
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'<' {
+            w.raw = raw_element_after(&text[i..], w.raw);
+        }
+        let body = match body {
+            Some(body) if bytes[i] == b'\n' && w.raw.is_none() => body,
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        w.write_str(&text[start..=i])?;
+        let (indent, rest) = split_indent(&text[i + 1..]);
+        if rest.starts_with(['\r', '\n']) {
+            // Blank lines are left without indentation.
+        } else if rest.is_empty() && is_last {
+            w.write_str(&body.outer)?;
+        } else {
+            let extra = indent_width(indent, options).saturating_sub(body.min_width);
+            w.write_str(&body.inner)?;
+            w.write_str(&indent_string(extra, options))?;
+        }
+        i += 1 + indent.len();
+        start = i;
+    }
+    return w.write_str(&text[start..]);
+}
+
+fn write_children<W: fmt::Write>(
+    children: &[Node],
+    body: Option<&BodyIndent>,
+    options: &Options,
+    w: &mut Writer<W>,
+) -> fmt::Result {
+    for (i, child) in children.iter().enumerate() {
+        match child {
+            Text(TextNode { value, .. }) => {
+                write_text(value, body, i + 1 == children.len(), options, w)?
+            }
+            _ => write_node(child, options, w)?,
+        }
+    }
+    return Ok(());
+}
+
+fn value_to_string(value: &AttributeValue, options: &Options) -> String {
+    // Tags parsed from the value are normalized like any other tag.
+    let text = match &value.children {
+        Some(children) => {
+            let mut text = String::new();
+            let mut w = Writer::new(&mut text, options);
+            w.pretty = false;
+            // Writing to a `String` does not fail.
+            write_children(children, None, options, &mut w).unwrap();
+            text
+        }
+        None => value.value.clone(),
+    };
+    return quote_value(&text, value.quote);
+}
+
+/// Return `name` of a tag in the case requested by `options.tag_case`.
+fn tag_name<'n>(name: &'n str, options: &Options) -> Cow<'n, str> {
+    return match options.tag_case {
+        NameCase::Preserve => Cow::Borrowed(name),
+        NameCase::Canonical => match CATALOG.tag(name) {
+            Some(tag) => Cow::Borrowed(tag.name),
+            None => Cow::Borrowed(name),
+        },
+        NameCase::Lower => Cow::Owned(name.to_lowercase()),
+    };
+}
+
+/// Return `name` of an attribute of `tag` in the case requested by
+/// `options.attribute_case`. The canonical case is the one in the tag's
+/// definition or, for modifiers, the modifier's.
+fn attribute_name<'n>(tag: &str, name: &'n str, options: &Options) -> Cow<'n, str> {
+    return match options.attribute_case {
+        NameCase::Preserve => Cow::Borrowed(name),
+        NameCase::Canonical => {
+            let canonical = CATALOG
+                .tag(tag)
+                .and_then(|tag| tag.attributes)
+                .and_then(|attributes| {
+                    attributes
+                        .iter()
+                        .find(|attribute| attribute.eq_ignore_ascii_case(name))
+                        .copied()
+                })
+                .or_else(|| CATALOG.modifier(name).map(|modifier| modifier.name));
+            Cow::Borrowed(canonical.unwrap_or(name))
+        }
+        NameCase::Lower => Cow::Owned(name.to_lowercase()),
+    };
+}
+
+fn write_attribute<W: fmt::Write>(
+    attr: &Attribute,
+    tag: &str,
+    options: &Options,
+    out: &mut W,
+) -> fmt::Result {
+    out.write_str(&attribute_name(tag, &attr.name, options))?;
+    if attr.syntax == AttributeSyntax::Valueless {
+        return Ok(());
+    }
+    for (i, value) in attr.values.iter().enumerate() {
+        out.write_str(if i == 0 { "=" } else { "," })?;
+        if attr.synthetic {
+            // Values set by the editing API are not known to be safe as written.
+            out.write_str(&quote_value(&value.value, value.quote))?;
+        } else if options.lossless {
+            let quote = quote_to_str(value.quote);
+            write!(out, "{}{}{}", quote, value.value, quote)?;
+        } else {
+            out.write_str(&value_to_string(value, options))?;
+        }
+    }
+    return Ok(());
+}
+
+fn is_modifier(name: &str) -> bool {
+    return CATALOG.modifier(name).is_some();
+}
+
+/// Return `attributes` in the order requested by `options.attribute_order`.
+fn order_attributes<'a>(attributes: &'a [Attribute], options: &Options) -> Vec<&'a Attribute> {
+    let mut ordered: Vec<&Attribute> = attributes.iter().collect();
+    if options.attribute_order == AttributeOrder::ArgumentsFirst {
+        // The sort is stable, so arguments and modifiers each keep their order.
+        ordered.sort_by_key(|attr| is_modifier(&attr.name));
+    }
+    return ordered;
+}
+
+/// Write the attributes of a tag followed by `tail`. In pretty mode, each
+/// attribute goes on its own line if the tag would not fit in `max_width`.
+fn write_attributes<W: fmt::Write>(
+    tag: &str,
+    attributes: &[Attribute],
+    tail: &str,
+    options: &Options,
+    w: &mut Writer<W>,
+) -> fmt::Result {
+    let attributes = order_attributes(attributes, options);
+    if w.pretty && w.raw.is_none() && attributes.len() > 1 {
+        let mut line = String::new();
+        for attr in &attributes {
+            line.push(' ');
+            write_attribute(attr, tag, options, &mut line)?;
+        }
+        let width = w.column + line.chars().count() + tail.chars().count();
+        if width > options.max_width {
+            let indent = w.indent.clone() + &indent_string(options.indent_width, options);
+            for attr in &attributes {
+                write!(w, "\n{}", indent)?;
+                write_attribute(attr, tag, options, w)?;
+            }
+            return w.write_str(tail);
+        }
+    }
+
+    for attr in &attributes {
+        if options.lossless && !w.pretty {
+            w.write_str(&attr.leading_whitespace)?;
+        } else {
+            w.write_char(' ')?;
+        }
+        write_attribute(attr, tag, options, w)?;
+    }
+    return w.write_str(tail);
+}
+
+fn write_function_tag<W: fmt::Write>(
+    node: &FunctionTagNode,
+    options: &Options,
+    w: &mut Writer<W>,
+) -> fmt::Result {
+    let (pre_sign, prefix, tail) = if options.lossless {
+        let pre_sign = if node.style == TagStyle::Dollar {
+            "$"
+        } else {
+            ""
+        };
+        (pre_sign, node.prefix.as_str(), node.tail.as_str())
+    } else if options.function_tag_style == FunctionTagStyle::Dollar {
+        ("$", options.prefix.as_str(), "$")
+    } else {
+        ("", options.prefix.as_str(), "/")
+    };
+    write!(
+        w,
+        "<{}{}{}",
+        pre_sign,
+        prefix,
+        tag_name(&node.name, options)
+    )?;
+    let tail = format!("{}>", tail);
+    return write_attributes(&node.name, &node.attributes, &tail, options, w);
+}
+
+fn write_block_tag<W: fmt::Write>(
+    node: &BlockTagNode,
+    options: &Options,
+    w: &mut Writer<W>,
+) -> fmt::Result {
+    let body = if w.pretty && w.raw.is_none() {
+        let outer = w.indent.clone();
+        Some(BodyIndent {
+            inner: outer.clone() + &indent_string(options.indent_width, options),
+            outer,
+            min_width: min_indent_width(&node.children, w.raw, options).unwrap_or(0),
+        })
+    } else {
+        None
+    };
+
+    if options.lossless {
+        let pre_sign = if node.style == TagStyle::Dollar {
+            "$"
+        } else {
+            ""
+        };
+        let name = tag_name(&node.name, options);
+        write!(w, "<{}{}{}", pre_sign, node.prefix, name)?;
+        let tail = format!("{}>", node.tail);
+        write_attributes(&node.name, &node.attributes, &tail, options, w)?;
+    } else {
+        write!(w, "<{}{}", options.prefix, tag_name(&node.name, options))?;
+        write_attributes(&node.name, &node.attributes, ">", options, w)?;
+    }
+    write_children(&node.children, body.as_ref(), options, w)?;
+    if !options.lossless {
+        return write!(w, "</{}{}>", options.prefix, tag_name(&node.name, options));
+    }
+    if !node.closing_name.is_empty() {
+        let closing_name = tag_name(&node.closing_name, options);
+        write!(w, "</{}{}>", node.closing_prefix, closing_name)?;
+    }
+    return Ok(());
+}
+
+fn write_node<W: fmt::Write>(node: &Node, options: &Options, w: &mut Writer<W>) -> fmt::Result {
+    match node {
+        Root(RootNode { children }) => {
+            write_children(children, None, options, w)?;
+        }
+        Text(TextNode { value, .. }) => {
+            write_text(value, None, false, options, w)?;
+        }
+        FunctionTag(node) => {
+            write_function_tag(node, options, w)?;
+        }
+        BlockTag(node) => {
+            write_block_tag(node, options, w)?;
+        }
+        Ignore(IgnoreNode {
+            name,
+            attributes,
+            value,
+            prefix,
+            style,
+            tail,
+            closing_prefix,
+            closing_name,
+            ..
+        }) if options.lossless => {
+            let pre_sign = if *style == TagStyle::Dollar { "$" } else { "" };
+            write!(w, "<{}{}{}", pre_sign, prefix, tag_name(name, options))?;
+            write_attributes(name, attributes, &format!("{}>", tail), options, w)?;
+            w.write_str(value)?;
+            if !closing_name.is_empty() {
+                let closing_name = tag_name(closing_name, options);
+                write!(w, "</{}{}>", closing_prefix, closing_name)?;
+            }
+        }
+        Ignore(IgnoreNode {
+            name,
+            attributes,
+            value,
+            ..
+        }) => {
+            let written_name = tag_name(name, options);
+            write!(w, "<{}{}", options.prefix, written_name)?;
+            write_attributes(name, attributes, ">", options, w)?;
+            write!(w, "{}</{}{}>", value, options.prefix, written_name)?;
+        }
+        Comment(CommentNode { value, .. }) => {
+            write!(w, "<!--{}-->", value)?;
+        }
+        Error(ErrorNode { value, .. }) => {
+            w.write_str(value)?;
+        }
+        Missing(_) => {}
+    }
+
+    return Ok(());
 }
 
 /// Serialize AST to MTML document.
@@ -53,58 +618,252 @@ fn attribute_to_string(attr: Attribute) -> String {
 /// ```
 pub fn serialize(node: Node, options: Option<Options>) -> String {
     let mut s = String::new();
-    let options = options.unwrap_or(Options {
-        prefix: "mt:".to_string(),
-        function_tag_style: FunctionTagStyle::Dollar,
+    // Writing to a `String` does not fail.
+    serialize_to(&node, &options.unwrap_or_default(), &mut s).unwrap();
+    return s;
+}
+
+/// Serialize AST to MTML document, writing to `out` without consuming the AST.
+///
+/// # Examples
+///
+/// ```
+/// use mtml_parser::{parse, serializer::{serialize_to, Options}};
+///
+/// let node = parse("<mt:Entries><mt:EntryTitle /></mt:Entries>").unwrap();
+/// let mut s = String::new();
+/// serialize_to(&node, &Options::default(), &mut s).unwrap();
+/// assert_eq!(s, "<mt:Entries><$mt:EntryTitle$></mt:Entries>");
+/// ```
+pub fn serialize_to<W: fmt::Write>(node: &Node, options: &Options, out: &mut W) -> fmt::Result {
+    return write_node(node, options, &mut Writer::new(out, options));
+}
+
+/// Adapts an `io::Write` to `fmt::Write`, keeping the I/O error that
+/// `fmt::Error` cannot carry.
+struct IoWriter<'w, W: io::Write> {
+    inner: &'w mut W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> fmt::Write for IoWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        return self.inner.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        });
+    }
+}
+
+/// Serialize AST to MTML document, writing to an `io::Write` such as a file
+/// or stdout. See `serialize_to`.
+pub fn serialize_to_writer<W: io::Write>(
+    node: &Node,
+    options: &Options,
+    out: &mut W,
+) -> io::Result<()> {
+    let mut writer = IoWriter {
+        inner: out,
+        error: None,
+    };
+    return serialize_to(node, options, &mut writer).map_err(|_| {
+        writer
+            .error
+            .unwrap_or_else(|| io::Error::other("formatter error"))
     });
+}
 
-    match node {
-        Root(RootNode { children }) => {
-            for child in children {
-                s.push_str(&serialize(child, Some(options.clone())));
+/// An attribute that `AttributeOrder::ArgumentsFirst` moves before a
+/// modifier although the tag catalogue does not list it as an argument of its
+/// tag. If it is a modifier unknown to the catalogue, e.g. one defined by a
+/// plugin, the move changes the order in which modifiers are applied.
+///
+/// For a tag whose arguments are not catalogued, every attribute moved before
+/// a modifier is reported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeOrderWarning {
+    pub tag: String,
+    pub attribute: String,
+    /// The first modifier the attribute is moved before.
+    pub modifier: String,
+    pub position: Position,
+}
+
+impl fmt::Display for AttributeOrderWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Moving attribute {} of mt:{} before modifier {} may change its meaning at {}",
+            self.attribute, self.tag, self.modifier, self.position
+        )
+    }
+}
+
+struct AttributeOrderChecker(Vec<AttributeOrderWarning>);
+
+impl AttributeOrderChecker {
+    fn check(&mut self, tag: &str, attributes: &[Attribute]) {
+        let arguments = CATALOG
+            .tag(tag)
+            .and_then(|tag| tag.attributes)
+            .unwrap_or(&[]);
+        let mut modifier = None;
+        for attr in attributes {
+            if is_modifier(&attr.name) {
+                modifier = modifier.or(Some(&attr.name));
+                continue;
             }
-        }
-        Text(TextNode { value, .. }) => {
-            s.push_str(value.as_str());
-        }
-        FunctionTag(FunctionTagNode {
-            name, attributes, ..
-        }) => {
-            let pre_sign = if options.function_tag_style == FunctionTagStyle::Dollar {
-                "$"
-            } else {
-                ""
-            };
-            let post_sign = if options.function_tag_style == FunctionTagStyle::Dollar {
-                "$"
-            } else {
-                "/"
-            };
-            s.push_str(&format!("<{}{}{}", pre_sign, options.prefix, name));
-            for attr in attributes {
-                s.push_str(&attribute_to_string(attr))
+            let is_argument = arguments
+                .iter()
+                .any(|argument| argument.eq_ignore_ascii_case(&attr.name));
+            if let (Some(modifier), false) = (modifier, is_argument) {
+                self.0.push(AttributeOrderWarning {
+                    tag: tag.to_string(),
+                    attribute: attr.name.clone(),
+                    modifier: modifier.clone(),
+                    position: Position {
+                        line: attr.line,
+                        column: attr.column,
+                        offset: attr.offset,
+                    },
+                });
             }
-            s.push_str(&format!("{}>", post_sign));
         }
-        BlockTag(BlockTagNode {
-            name,
-            children,
-            attributes,
-            ..
-        }) => {
-            s.push_str(&format!("<{}{}", options.prefix, name));
-            for attr in attributes {
-                s.push_str(&attribute_to_string(attr))
-            }
-            s.push_str(">");
-            for child in children {
-                s.push_str(&serialize(child, Some(options.clone())));
-            }
-            s.push_str(&format!("</{}{}>", options.prefix, name));
+    }
+}
+
+impl Visit for AttributeOrderChecker {
+    fn visit_function_tag(&mut self, node: &FunctionTagNode) {
+        self.check(&node.name, &node.attributes);
+        walk_function_tag(self, node);
+    }
+
+    fn visit_block_tag(&mut self, node: &BlockTagNode) {
+        self.check(&node.name, &node.attributes);
+        walk_block_tag(self, node);
+    }
+
+    fn visit_ignore(&mut self, node: &IgnoreNode) {
+        self.check(&node.name, &node.attributes);
+        walk_ignore(self, node);
+    }
+}
+
+/// Return the attributes whose move by `AttributeOrder::ArgumentsFirst` may
+/// change the meaning of the template.
+///
+/// # Examples
+///
+/// ```
+/// use mtml_parser::{parse, serializer::attribute_order_warnings};
+///
+/// let node = parse(r#"<$mt:EntryTitle encode_html="1" my_filter="1"$>"#).unwrap();
+/// let warnings = attribute_order_warnings(&node);
+/// assert_eq!(warnings[0].attribute, "my_filter");
+/// assert_eq!(warnings[0].modifier, "encode_html");
+/// ```
+pub fn attribute_order_warnings(node: &Node) -> Vec<AttributeOrderWarning> {
+    let mut checker = AttributeOrderChecker(vec![]);
+    checker.visit_node(node);
+    return checker.0;
+}
+
+/// Serialization of a single AST node or attribute back to MTML.
+///
+/// `Display` uses the default `Options`, so `to_string()` gives the same
+/// result as `to_mtml(&Options::default())`.
+///
+/// # Examples
+///
+/// ```
+/// use mtml_parser::{ast::Node, parse, serializer::{Options, ToMtml}};
+///
+/// let node = parse(r#"<MTEntryTitle encode_html="1">"#).unwrap();
+/// if let Node::Root(root) = node {
+///     let tag = &root.children[0];
+///     assert_eq!(tag.to_string(), r#"<$mt:EntryTitle encode_html="1"$>"#);
+///     assert_eq!(tag.attributes().unwrap()[0].to_string(), r#"encode_html="1""#);
+///
+///     let options = Options { lossless: true, ..Default::default() };
+///     assert_eq!(tag.to_mtml(&options), r#"<MTEntryTitle encode_html="1">"#);
+/// }
+/// ```
+pub trait ToMtml {
+    fn write_mtml<W: fmt::Write>(&self, options: &Options, out: &mut W) -> fmt::Result;
+
+    fn to_mtml(&self, options: &Options) -> String {
+        let mut s = String::new();
+        // Writing to a `String` does not fail.
+        self.write_mtml(options, &mut s).unwrap();
+        return s;
+    }
+}
+
+impl ToMtml for Node {
+    fn write_mtml<W: fmt::Write>(&self, options: &Options, out: &mut W) -> fmt::Result {
+        return serialize_to(self, options, out);
+    }
+}
+
+impl ToMtml for FunctionTagNode {
+    fn write_mtml<W: fmt::Write>(&self, options: &Options, out: &mut W) -> fmt::Result {
+        return write_function_tag(self, options, &mut Writer::new(out, options));
+    }
+}
+
+impl ToMtml for BlockTagNode {
+    fn write_mtml<W: fmt::Write>(&self, options: &Options, out: &mut W) -> fmt::Result {
+        return write_block_tag(self, options, &mut Writer::new(out, options));
+    }
+}
+
+/// Writes `name="value"`, without the whitespace before the attribute. As the
+/// tag is not known, only modifiers have a canonical name.
+impl ToMtml for Attribute {
+    fn write_mtml<W: fmt::Write>(&self, options: &Options, out: &mut W) -> fmt::Result {
+        return write_attribute(self, "", options, out);
+    }
+}
+
+/// Writes the value with its quotes.
+impl ToMtml for AttributeValue {
+    fn write_mtml<W: fmt::Write>(&self, options: &Options, out: &mut W) -> fmt::Result {
+        if options.lossless {
+            let quote = quote_to_str(self.quote);
+            return write!(out, "{}{}{}", quote, self.value, quote);
         }
+        return out.write_str(&value_to_string(self, options));
     }
+}
 
-    return s;
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return self.write_mtml(&Options::default(), f);
+    }
+}
+
+impl fmt::Display for FunctionTagNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return self.write_mtml(&Options::default(), f);
+    }
+}
+
+impl fmt::Display for BlockTagNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return self.write_mtml(&Options::default(), f);
+    }
+}
+
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return self.write_mtml(&Options::default(), f);
+    }
+}
+
+impl fmt::Display for AttributeValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return self.write_mtml(&Options::default(), f);
+    }
 }
 
 #[cfg(test)]
@@ -131,7 +890,7 @@ mod tests {
 <html>
   <body>
     <mt:Entries limit="10">
-      <$mt:EntryTitle encode_html="1"$>
+      <$mt:EntryTitle encode_html='1'$>
     </mt:Entries>
   </body>
 </html>"#
@@ -146,6 +905,7 @@ mod tests {
             Some(Options {
                 prefix: "mt:".to_string(),
                 function_tag_style: FunctionTagStyle::SelfClosing,
+                ..Default::default()
             }),
         );
         assert_eq!(
@@ -154,7 +914,7 @@ mod tests {
 <html>
   <body>
     <mt:Entries limit="10">
-      <mt:EntryTitle encode_html="1"/>
+      <mt:EntryTitle encode_html='1'/>
     </mt:Entries>
   </body>
 </html>"#
@@ -169,6 +929,7 @@ mod tests {
             Some(Options {
                 prefix: "MT".to_string(),
                 function_tag_style: FunctionTagStyle::Dollar,
+                ..Default::default()
             }),
         );
         assert_eq!(
@@ -177,7 +938,7 @@ mod tests {
 <html>
   <body>
     <MTEntries limit="10">
-      <$MTEntryTitle encode_html="1"$>
+      <$MTEntryTitle encode_html='1'$>
     </MTEntries>
   </body>
 </html>"#
@@ -202,6 +963,7 @@ mod tests {
             Some(Options {
                 prefix: "mt:".to_string(),
                 function_tag_style: FunctionTagStyle::Dollar,
+                ..Default::default()
             }),
         );
         assert_eq!(
@@ -216,4 +978,269 @@ mod tests {
 </html>"#
         )
     }
+
+    #[test]
+    fn test_serialize_lossless() {
+        let inputs = [
+            INPUT,
+            r#"<MTEntries lastn="3"><$MTEntryTitle$> <mtEntryBody /></mtentries>"#,
+            r#"<mt:If  name="a"   eq='b' ><mt:Else ><$mt:Var name="x" replace="a",'b' $></MT:IF>"#,
+            "<MTIgnore >\n<mt:Entries>\n</mtignore>",
+            "<mt:Entries lastn=5 sort_order=descend><$mt:Var foo$><mt:EntryTitle encode_html/></mt:Entries>",
+            "<mt:Entries>\n  <mt:EntryTitle>\n</MT:Entries >",
+        ];
+        for input in inputs {
+            let (root, _) = parse_with_recovery(input);
+            let serialized = serialize(
+                root,
+                Some(Options {
+                    lossless: true,
+                    ..Default::default()
+                }),
+            );
+            assert_eq!(serialized, input);
+        }
+    }
+
+    #[test]
+    fn test_serialize_ignore_and_comment() {
+        let root = ParserConfig::default()
+            .html_comments(true)
+            .build()
+            .parse("<MTIgnore><mtEntries></MTIgnore><!-- <mtEntryTitle> -->")
+            .unwrap();
+        assert_eq!(
+            serialize(root, None),
+            "<mt:Ignore><mtEntries></mt:Ignore><!-- <mtEntryTitle> -->"
+        );
+    }
+
+    #[test]
+    fn test_serialize_to() {
+        let root = parse(INPUT).unwrap();
+        let options = Options {
+            lossless: true,
+            ..Default::default()
+        };
+        let mut s = String::new();
+        serialize_to(&root, &options, &mut s).unwrap();
+        assert_eq!(s, INPUT);
+
+        let mut bytes = vec![];
+        serialize_to_writer(&root, &Options::default(), &mut bytes).unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap(), serialize(root, None));
+    }
+
+    #[test]
+    fn test_display() {
+        let root =
+            parse(r#"<MTEntries lastn=5><mtEntryTitle replace="a",'b'/></MTEntries>"#).unwrap();
+        let entries = match &root {
+            Root(RootNode { children }) => match &children[0] {
+                BlockTag(node) => node,
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+        assert_eq!(root.to_string(), entries.to_string());
+        assert_eq!(
+            entries.to_string(),
+            r#"<mt:Entries lastn=5><$mt:EntryTitle replace="a",'b'$></mt:Entries>"#
+        );
+        let title = match &entries.children[0] {
+            FunctionTag(node) => node,
+            _ => unreachable!(),
+        };
+        assert_eq!(title.to_string(), r#"<$mt:EntryTitle replace="a",'b'$>"#);
+        assert_eq!(title.attributes[0].to_string(), r#"replace="a",'b'"#);
+        assert_eq!(title.attributes[0].values[1].to_string(), "'b'");
+        assert_eq!(entries.attributes[0].values[0].to_string(), "5");
+    }
+
+    fn pretty(input: &str, options: Options) -> String {
+        return serialize(
+            parse(input).unwrap(),
+            Some(Options {
+                pretty: true,
+                ..options
+            }),
+        );
+    }
+
+    #[test]
+    fn test_serialize_pretty() {
+        let input = "<ul>
+<mt:Entries lastn=\"3\">
+<li>
+<mt:If name=\"x\">
+<b><$mt:EntryTitle$></b>
+
+<pre>
+  <mt:Var name=\"y\">
+</pre>
+    </mt:If>
+</li>
+    </mt:Entries>
+</ul>";
+        assert_eq!(
+            pretty(input, Default::default()),
+            "<ul>
+<mt:Entries lastn=\"3\">
+  <li>
+  <mt:If name=\"x\">
+    <b><$mt:EntryTitle$></b>
+
+    <pre>
+  <$mt:Var name=\"y\"$>
+</pre>
+  </mt:If>
+  </li>
+</mt:Entries>
+</ul>"
+        );
+        assert_eq!(
+            pretty(
+                "<mt:Entries>\n<mt:If name=\"x\">\n<p>\n</mt:If>\n</mt:Entries>",
+                Options {
+                    indent_tabs: true,
+                    ..Default::default()
+                }
+            ),
+            "<mt:Entries>\n\t<mt:If name=\"x\">\n\t\t<p>\n\t</mt:If>\n</mt:Entries>"
+        );
+        assert_eq!(
+            pretty("<mt:If name=\"x\">a</mt:If>", Default::default()),
+            "<mt:If name=\"x\">a</mt:If>"
+        );
+    }
+
+    #[test]
+    fn test_serialize_pretty_wrap_attributes() {
+        let input = "<mt:Entries>\n<mt:Var name=\"title\" value=\"<$mt:EntryTitle$>\" encode_html=\"1\">\n</mt:Entries>";
+        let options = Options {
+            indent_width: 4,
+            max_width: 40,
+            ..Default::default()
+        };
+        assert_eq!(
+            pretty(input, options.clone()),
+            "<mt:Entries>
+    <$mt:Var
+        name=\"title\"
+        value=\"<$mt:EntryTitle$>\"
+        encode_html=\"1\"$>
+</mt:Entries>"
+        );
+        // Pretty output is stable.
+        let wrapped = pretty(input, options.clone());
+        assert_eq!(pretty(&wrapped, options), wrapped);
+        assert_eq!(
+            pretty(
+                "<pre><mt:Var name=\"title\" value=\"<$mt:EntryTitle$>\" encode_html=\"1\"></pre>",
+                Options {
+                    max_width: 40,
+                    ..Default::default()
+                }
+            ),
+            "<pre><$mt:Var name=\"title\" value=\"<$mt:EntryTitle$>\" encode_html=\"1\"$></pre>"
+        );
+    }
+
+    #[test]
+    fn test_serialize_name_case() {
+        let input = r#"<MTIFNONEMPTY TAG="EntryTitle"><mt:entrytitle Encode_HTML="1"><$mt:MyPluginTag Foo="1"$></mtIfNonEmpty>"#;
+        let serialize_with = |lossless, tag_case, attribute_case| {
+            serialize(
+                parse(input).unwrap(),
+                Some(Options {
+                    lossless,
+                    tag_case,
+                    attribute_case,
+                    ..Default::default()
+                }),
+            )
+        };
+        assert_eq!(
+            serialize_with(false, NameCase::Canonical, NameCase::Canonical),
+            r#"<mt:IfNonEmpty tag="EntryTitle"><$mt:EntryTitle encode_html="1"$><$mt:MyPluginTag Foo="1"$></mt:IfNonEmpty>"#
+        );
+        assert_eq!(
+            serialize_with(true, NameCase::Lower, NameCase::Lower),
+            r#"<MTifnonempty tag="EntryTitle"><mt:entrytitle encode_html="1"><$mt:myplugintag foo="1"$></mtifnonempty>"#
+        );
+        assert_eq!(
+            serialize_with(true, NameCase::Preserve, NameCase::Preserve),
+            input
+        );
+    }
+
+    #[test]
+    fn test_serialize_attribute_order() {
+        let input = r#"<mt:Entries trim="1" lastn="3"><$mt:Date utc="1" format="%Y" encode_html="1" trim="1"$><$mt:Var name="x" replace="a","b" my_filter="1" escape="html"$><$mt:EntryTitle encode_html="1" my_filter="1"$></mt:Entries>"#;
+        let options = Options {
+            attribute_order: AttributeOrder::ArgumentsFirst,
+            ..Default::default()
+        };
+        assert_eq!(
+            serialize(parse(input).unwrap(), Some(options.clone())),
+            r#"<mt:Entries lastn="3" trim="1"><$mt:Date utc="1" format="%Y" encode_html="1" trim="1"$><$mt:Var name="x" my_filter="1" replace="a","b" escape="html"$><$mt:EntryTitle my_filter="1" encode_html="1"$></mt:Entries>"#
+        );
+
+        let root = parse(input).unwrap();
+        let warnings = attribute_order_warnings(&root);
+        assert_eq!(
+            warnings
+                .iter()
+                .map(|w| (w.tag.as_str(), w.attribute.as_str(), w.modifier.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Var", "my_filter", "replace"),
+                ("EntryTitle", "my_filter", "encode_html")
+            ]
+        );
+        assert_eq!(warnings[0].position.column, 122);
+    }
+
+    #[test]
+    fn test_quote_value() {
+        assert_eq!(quote_value("a", QuoteStyle::Double), r#""a""#);
+        assert_eq!(quote_value("a", QuoteStyle::Single), "'a'");
+        assert_eq!(quote_value("a", QuoteStyle::None), "a");
+        assert_eq!(quote_value("a b", QuoteStyle::None), r#""a b""#);
+        assert_eq!(quote_value("", QuoteStyle::None), r#""""#);
+        assert_eq!(quote_value("a/", QuoteStyle::None), r#""a/""#);
+        assert_eq!(
+            quote_value(r#"say "hi""#, QuoteStyle::Double),
+            r#"'say "hi"'"#
+        );
+        assert_eq!(quote_value("it's", QuoteStyle::Single), r#""it's""#);
+        assert_eq!(
+            quote_value(r#"it's "hi""#, QuoteStyle::Single),
+            r#""it's &quot;hi&quot;""#
+        );
+    }
+
+    #[test]
+    fn test_serialize_quote_style() {
+        let root = parse(r#"<mt:Entries lastn=5 sort_by='title'><$mt:Var name="x" replace='"',"'"$></mt:Entries>"#).unwrap();
+        assert_eq!(
+            serialize(root, None),
+            r#"<mt:Entries lastn=5 sort_by='title'><$mt:Var name="x" replace='"',"'"$></mt:Entries>"#
+        );
+    }
+
+    #[test]
+    fn test_serialize_attribute_tags() {
+        let input =
+            r#"<mt:SetVar name="title" value="<MTEntryTitle encode_html='1'/> - <mtBlogName>">"#;
+        let root = ParserConfig::default()
+            .attribute_tags(true)
+            .build()
+            .parse(input)
+            .unwrap();
+        assert_eq!(
+            serialize(root, None),
+            r#"<$mt:SetVar name="title" value="<$mt:EntryTitle encode_html='1'$> - <$mt:BlogName$>"$>"#
+        );
+    }
 }
@@ -1,462 +1,1206 @@
 use once_cell::sync::Lazy;
-use std::{collections::HashSet, sync::Mutex};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, collections::HashSet, sync::Mutex};
+
+/// How a tag is used in a template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TagKind {
+    /// A tag without a body, such as `<mt:EntryTitle>`.
+    Function,
+    /// A tag with a body, such as `<mt:Entries>...</mt:Entries>`.
+    Block,
+    /// A block tag whose body may contain `<mt:Else>` and `<mt:ElseIf>`, such as `<mt:If>`.
+    Conditional,
+}
+
+/// A Movable Type release with its own set of tags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum MtVersion {
+    Mt6,
+    Mt7,
+    Mt8,
+}
+
+/// A Movable Type distribution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Edition {
+    Standard,
+    /// Standard with the Commercial Pack, which adds custom fields.
+    CommercialPack,
+}
+
+/// A tag known to the catalogue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TagDefinition {
+    /// Canonical name without prefix, e.g. `EntryTitle`.
+    pub name: &'static str,
+    pub kind: TagKind,
+    /// Attributes accepted by the tag besides global modifiers, or `None` if
+    /// the accepted attributes are not catalogued.
+    pub attributes: Option<&'static [&'static str]>,
+    pub required_attributes: &'static [&'static str],
+    /// First version with the tag.
+    pub since: MtVersion,
+    /// First version without the tag, if it has been removed.
+    pub removed_in: Option<MtVersion>,
+    /// Whether the tag requires the Commercial Pack.
+    pub commercial_pack: bool,
+}
+
+impl TagDefinition {
+    const fn new(name: &'static str, kind: TagKind) -> Self {
+        return TagDefinition {
+            name,
+            kind,
+            attributes: None,
+            required_attributes: &[],
+            since: MtVersion::Mt6,
+            removed_in: None,
+            commercial_pack: false,
+        };
+    }
+
+    const fn attributes(self, attributes: &'static [&'static str]) -> Self {
+        return TagDefinition {
+            attributes: Some(attributes),
+            ..self
+        };
+    }
+
+    const fn required(self, required_attributes: &'static [&'static str]) -> Self {
+        return TagDefinition {
+            required_attributes,
+            ..self
+        };
+    }
+
+    const fn since(self, since: MtVersion) -> Self {
+        return TagDefinition { since, ..self };
+    }
+
+    const fn removed_in(self, version: MtVersion) -> Self {
+        return TagDefinition {
+            removed_in: Some(version),
+            ..self
+        };
+    }
+
+    const fn commercial_pack(self) -> Self {
+        return TagDefinition {
+            commercial_pack: true,
+            ..self
+        };
+    }
+
+    /// Return whether the tag exists in the given version and edition.
+    pub fn is_available_in(&self, version: MtVersion, edition: Edition) -> bool {
+        return self.since <= version
+            && self
+                .removed_in
+                .is_none_or(|removed_in| version < removed_in)
+            && (!self.commercial_pack || edition == Edition::CommercialPack);
+    }
+
+    /// Return whether `name` is one of the tag's own attributes.
+    ///
+    /// Always true when the tag's attributes are not catalogued.
+    pub fn accepts_attribute(&self, name: &str) -> bool {
+        return match self.attributes {
+            Some(attributes) => attributes.iter().any(|a| a.eq_ignore_ascii_case(name)),
+            None => true,
+        };
+    }
+}
+
+/// A global modifier such as `encode_html`, usable as an attribute of any tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModifierDefinition {
+    pub name: &'static str,
+    /// Number of comma-separated values the modifier takes, e.g. 2 for
+    /// `regex_replace="/foo/","bar"`.
+    pub arity: usize,
+}
+
+const fn function(name: &'static str) -> TagDefinition {
+    return TagDefinition::new(name, TagKind::Function);
+}
+
+const fn block(name: &'static str) -> TagDefinition {
+    return TagDefinition::new(name, TagKind::Block);
+}
+
+const fn conditional(name: &'static str) -> TagDefinition {
+    return TagDefinition::new(name, TagKind::Conditional);
+}
+
+const fn modifier(name: &'static str, arity: usize) -> ModifierDefinition {
+    return ModifierDefinition { name, arity };
+}
+
+const DATE_ATTRIBUTES: &[&str] = &["format", "format_name", "language", "relative", "utc"];
+const VAR_ATTRIBUTES: &[&str] = &[
+    "name", "default", "function", "glue", "index", "key", "op", "value",
+];
+const SET_VAR_ATTRIBUTES: &[&str] = &[
+    "name", "value", "op", "append", "prepend", "function", "index", "key",
+];
+const ENTRIES_ATTRIBUTES: &[&str] = &[
+    "lastn",
+    "limit",
+    "offset",
+    "sort_by",
+    "sort_order",
+    "category",
+    "categories",
+    "include_subcategories",
+    "tag",
+    "tags",
+    "author",
+    "days",
+    "id",
+    "unique",
+    "recently_commented_on",
+    "include_blogs",
+    "exclude_blogs",
+    "include_websites",
+    "blog_ids",
+    "site_ids",
+    "class_type",
+    "namespace",
+    "scored_by",
+    "min_score",
+    "max_score",
+    "min_rate",
+    "max_rate",
+    "min_count",
+    "max_count",
+    "glue",
+];
+const PAGES_ATTRIBUTES: &[&str] = &[
+    "lastn",
+    "limit",
+    "offset",
+    "sort_by",
+    "sort_order",
+    "folder",
+    "include_subfolders",
+    "no_folder",
+    "tag",
+    "tags",
+    "author",
+    "days",
+    "id",
+    "include_blogs",
+    "exclude_blogs",
+    "include_websites",
+    "blog_ids",
+    "site_ids",
+    "glue",
+];
+const CATEGORIES_ATTRIBUTES: &[&str] = &[
+    "show_empty",
+    "lastn",
+    "sort_by",
+    "sort_order",
+    "glue",
+    "include_blogs",
+    "exclude_blogs",
+    "blog_ids",
+    "site_ids",
+];
+const ASSETS_ATTRIBUTES: &[&str] = &[
+    "type",
+    "file_ext",
+    "lastn",
+    "limit",
+    "offset",
+    "sort_by",
+    "sort_order",
+    "tag",
+    "tags",
+    "author",
+    "days",
+    "assets_per_row",
+    "namespace",
+    "include_blogs",
+    "exclude_blogs",
+    "blog_ids",
+    "site_ids",
+    "scored_by",
+    "min_score",
+    "max_score",
+    "min_rate",
+    "max_rate",
+    "min_count",
+    "max_count",
+];
+// `<mt:If tag="...">` passes the remaining attributes to the tested tag.
+const IF_ATTRIBUTES: &[&str] = &[
+    "name", "tag", "eq", "ne", "gt", "ge", "lt", "le", "like", "test", "op", "value", "index",
+    "key",
+];
+
+static TAGS: &[TagDefinition] = &[
+    // Function tags.
+    function("AdminCGIPath"),
+    function("AdminScript"),
+    function("App:ActionBar"),
+    function("App:Link"),
+    function("App:ListFilters"),
+    function("App:PageActions"),
+    function("ArchiveCategory"),
+    function("ArchiveCount"),
+    function("ArchiveDate").attributes(DATE_ATTRIBUTES),
+    function("ArchiveDateEnd"),
+    function("ArchiveFile"),
+    function("ArchiveLabel"),
+    function("ArchiveLink"),
+    function("ArchiveTitle"),
+    function("ArchiveType"),
+    function("ArchiveTypeLabel"),
+    function("AssetAddedBy"),
+    function("AssetBlogID"),
+    function("AssetCount"),
+    function("AssetDateAdded"),
+    function("AssetDescription"),
+    function("AssetFileExt"),
+    function("AssetFileName"),
+    function("AssetFilePath"),
+    function("AssetID"),
+    function("AssetLabel"),
+    function("AssetLink"),
+    function("AssetMimeType"),
+    function("AssetProperty"),
+    function("AssetRank"),
+    function("AssetScore"),
+    function("AssetScoreAvg"),
+    function("AssetScoreCount"),
+    function("AssetScoreHigh"),
+    function("AssetScoreLow"),
+    function("AssetSiteID"),
+    function("AssetThumbnailLink"),
+    function("AssetThumbnailURL"),
+    function("AssetType"),
+    function("AssetURL"),
+    function("AtomScript"),
+    function("AudioCustomFieldDescription").commercial_pack(),
+    function("AudioCustomFieldName").commercial_pack(),
+    function("AudioCustomFieldValue").commercial_pack(),
+    function("AuthorAuthIconURL"),
+    function("AuthorAuthType"),
+    function("AuthorBasename"),
+    function("AuthorCommentCount"),
+    function("AuthorContentCount"),
+    function("AuthorCustomFieldDescription").commercial_pack(),
+    function("AuthorCustomFieldName").commercial_pack(),
+    function("AuthorCustomFieldValue").commercial_pack(),
+    function("AuthorDisplayName"),
+    function("AuthorEmail"),
+    function("AuthorEntriesCount"),
+    function("AuthorEntryCount"),
+    function("AuthorFavoriteEntries"),
+    function("AuthorFollowersCount"),
+    function("AuthorFollowingCount"),
+    function("AuthorFollowLink"),
+    function("AuthorID"),
+    function("AuthorName"),
+    function("AuthorRank"),
+    function("AuthorScore"),
+    function("AuthorScoreAvg"),
+    function("AuthorScoreCount"),
+    function("AuthorScoreHigh"),
+    function("AuthorScoreLow"),
+    function("AuthorUnfollowLink"),
+    function("AuthorURL"),
+    function("AuthorUserpic"),
+    function("AuthorUserpicURL"),
+    function("BlockEditorBlockAsset").since(MtVersion::Mt7),
+    function("BlockEditorBlocks").since(MtVersion::Mt7),
+    function("BlogArchiveURL"),
+    function("BlogCategoryCount"),
+    function("BlogCCLicenseImage"),
+    function("BlogCCLicenseURL"),
+    function("BlogCommentCount"),
+    function("BlogCustomFieldDescription").commercial_pack(),
+    function("BlogCustomFieldName").commercial_pack(),
+    function("BlogCustomFieldValue").commercial_pack(),
+    function("BlogDateLanguage"),
+    function("BlogDescription"),
+    function("BlogEntryCount"),
+    function("BlogFileExtension"),
+    function("BlogHost"),
+    function("BlogID"),
+    function("BlogLanguage"),
+    function("BlogName"),
+    function("BlogPageCount"),
+    function("BlogPingCount"),
+    function("BlogRelativeURL"),
+    function("BlogSitePath"),
+    function("BlogTemplateSetID"),
+    function("BlogThemeID"),
+    function("BlogTimezone"),
+    function("BlogURL"),
+    function("BuildTemplateID"),
+    function("CalendarCellNumber"),
+    function("CalendarDate").attributes(DATE_ATTRIBUTES),
+    function("CalendarDay"),
+    function("CanonicalLink"),
+    function("CanonicalURL"),
+    function("CaptchaFields"),
+    function("CategoryArchiveLink"),
+    function("CategoryBasename"),
+    function("CategoryCommentCount"),
+    function("CategoryCount"),
+    function("CategoryCustomFieldDescription").commercial_pack(),
+    function("CategoryCustomFieldName").commercial_pack(),
+    function("CategoryCustomFieldValue").commercial_pack(),
+    function("CategoryDescription"),
+    function("CategoryID"),
+    function("CategoryLabel"),
+    function("CategorySetName").since(MtVersion::Mt7),
+    function("CategoryTrackbackCount"),
+    function("CategoryTrackbackLink"),
+    function("CCLicenseRDF"),
+    function("CGIHost"),
+    function("CGIPath"),
+    function("CGIRelativeURL"),
+    function("CGIServerPath"),
+    function("CommentAuthor"),
+    function("CommentAuthorIdentity"),
+    function("CommentAuthorLink"),
+    function("CommentBlogID"),
+    function("CommentBody"),
+    function("CommentCustomFieldDescription").commercial_pack(),
+    function("CommentCustomFieldName").commercial_pack(),
+    function("CommentCustomFieldValue").commercial_pack(),
+    function("CommentDate").attributes(DATE_ATTRIBUTES),
+    function("CommentEmail"),
+    function("CommentEntryID"),
+    function("CommenterAuthIconURL"),
+    function("CommenterAuthType"),
+    function("CommenterEmail"),
+    function("CommenterID"),
+    function("CommenterName"),
+    function("CommenterNameThunk"),
+    function("CommenterURL"),
+    function("CommenterUsername"),
+    function("CommenterUserpic"),
+    function("CommenterUserpicURL"),
+    function("CommentID"),
+    function("CommentIP"),
+    function("CommentLink"),
+    function("CommentName"),
+    function("CommentOrderNumber"),
+    function("CommentParentID"),
+    function("CommentPreviewAuthor"),
+    function("CommentPreviewAuthorLink"),
+    function("CommentPreviewBody"),
+    function("CommentPreviewDate").attributes(DATE_ATTRIBUTES),
+    function("CommentPreviewEmail"),
+    function("CommentPreviewIP"),
+    function("CommentPreviewIsStatic"),
+    function("CommentPreviewURL"),
+    function("CommentRank"),
+    function("CommentRepliesRecurse"),
+    function("CommentReplyToLink"),
+    function("CommentScore"),
+    function("CommentScoreAvg"),
+    function("CommentScoreCount"),
+    function("CommentScoreHigh"),
+    function("CommentScoreLow"),
+    function("CommentScript"),
+    function("CommentSiteID"),
+    function("CommentURL"),
+    function("CommunityScript"),
+    function("ConfigFile"),
+    function("ContentAuthorDisplayName").since(MtVersion::Mt7),
+    function("ContentAuthorEmail").since(MtVersion::Mt7),
+    function("ContentAuthorID").since(MtVersion::Mt7),
+    function("ContentAuthorLink").since(MtVersion::Mt7),
+    function("ContentAuthorURL").since(MtVersion::Mt7),
+    function("ContentAuthorUsername").since(MtVersion::Mt7),
+    function("ContentAuthorUserpic").since(MtVersion::Mt7),
+    function("ContentAuthorUserpicURL").since(MtVersion::Mt7),
+    function("ContentCreatedDate")
+        .attributes(DATE_ATTRIBUTES)
+        .since(MtVersion::Mt7),
+    function("ContentDataSearchScript").since(MtVersion::Mt7),
+    function("ContentDate")
+        .attributes(DATE_ATTRIBUTES)
+        .since(MtVersion::Mt7),
+    function("ContentFieldValue").since(MtVersion::Mt7),
+    function("ContentID").since(MtVersion::Mt7),
+    function("ContentIdentifier").since(MtVersion::Mt7),
+    function("ContentLabel").since(MtVersion::Mt7),
+    function("ContentModifiedAuthorDisplayName").since(MtVersion::Mt7),
+    function("ContentModifiedAuthorEmail").since(MtVersion::Mt7),
+    function("ContentModifiedAuthorID").since(MtVersion::Mt7),
+    function("ContentModifiedAuthorLink").since(MtVersion::Mt7),
+    function("ContentModifiedAuthorURL").since(MtVersion::Mt7),
+    function("ContentModifiedAuthorUsername").since(MtVersion::Mt7),
+    function("ContentModifiedAuthorUserpic").since(MtVersion::Mt7),
+    function("ContentModifiedAuthorUserpicAsset").since(MtVersion::Mt7),
+    function("ContentModifiedAuthorUserpicURL").since(MtVersion::Mt7),
+    function("ContentModifiedDate")
+        .attributes(DATE_ATTRIBUTES)
+        .since(MtVersion::Mt7),
+    function("ContentPermalink").since(MtVersion::Mt7),
+    function("ContentsCount").since(MtVersion::Mt7),
+    function("ContentSiteDescription").since(MtVersion::Mt7),
+    function("ContentSiteID").since(MtVersion::Mt7),
+    function("ContentSiteName").since(MtVersion::Mt7),
+    function("ContentSiteURL").since(MtVersion::Mt7),
+    function("ContentStatus").since(MtVersion::Mt7),
+    function("ContentTypeDescription").since(MtVersion::Mt7),
+    function("ContentTypeID").since(MtVersion::Mt7),
+    function("ContentTypeName").since(MtVersion::Mt7),
+    function("ContentTypeUniqueID").since(MtVersion::Mt7),
+    function("ContentUniqueID").since(MtVersion::Mt7),
+    function("ContentUnpublishedDate")
+        .attributes(DATE_ATTRIBUTES)
+        .since(MtVersion::Mt7),
+    function("CurrentPage"),
+    function("CustomFieldBasename").commercial_pack(),
+    function("CustomFieldDescription").commercial_pack(),
+    function("CustomFieldHTML").commercial_pack(),
+    function("CustomFieldName").commercial_pack(),
+    function("CustomFieldValue").commercial_pack(),
+    function("DataAPIScript"),
+    function("DataAPIVersion"),
+    function("Date").attributes(&["ts", "format", "format_name", "language", "relative", "utc"]),
+    function("DefaultLanguage"),
+    function("Else").attributes(IF_ATTRIBUTES),
+    function("ElseIf").attributes(IF_ATTRIBUTES),
+    function("EntriesCount"),
+    function("EntryAtomID"),
+    function("EntryAuthor"),
+    function("EntryAuthorDisplayName"),
+    function("EntryAuthorEmail"),
+    function("EntryAuthorID"),
+    function("EntryAuthorLink"),
+    function("EntryAuthorNickname"),
+    function("EntryAuthorURL"),
+    function("EntryAuthorUsername"),
+    function("EntryAuthorUserpic"),
+    function("EntryAuthorUserpicURL"),
+    function("EntryBasename"),
+    function("EntryBlogDescription"),
+    function("EntryBlogID"),
+    function("EntryBlogName"),
+    function("EntryBlogURL"),
+    function("EntryBody"),
+    function("EntryCategory"),
+    function("EntryClass"),
+    function("EntryClassLabel"),
+    function("EntryCommentCount"),
+    function("EntryCreatedDate").attributes(DATE_ATTRIBUTES),
+    function("EntryCustomFieldDescription").commercial_pack(),
+    function("EntryCustomFieldName").commercial_pack(),
+    function("EntryCustomFieldValue").commercial_pack(),
+    function("EntryDate").attributes(DATE_ATTRIBUTES),
+    function("EntryEditLink"),
+    function("EntryExcerpt"),
+    function("EntryFlag"),
+    function("EntryID"),
+    function("EntryKeywords"),
+    function("EntryLink"),
+    function("EntryModifiedAuthorDisplayName"),
+    function("EntryModifiedAuthorEmail"),
+    function("EntryModifiedAuthorID"),
+    function("EntryModifiedAuthorLink"),
+    function("EntryModifiedAuthorURL"),
+    function("EntryModifiedAuthorUsername"),
+    function("EntryModifiedAuthorUserpic"),
+    function("EntryModifiedAuthorUserpicAsset"),
+    function("EntryModifiedAuthorUserpicURL"),
+    function("EntryModifiedDate").attributes(DATE_ATTRIBUTES),
+    function("EntryMore"),
+    function("EntryPermalink"),
+    function("EntryRank"),
+    function("EntryRecommendedTotal"),
+    function("EntryRecommendVoteLink"),
+    function("EntryScore"),
+    function("EntryScoreAvg"),
+    function("EntryScoreCount"),
+    function("EntryScoreHigh"),
+    function("EntryScoreLow"),
+    function("EntrySiteDescription"),
+    function("EntrySiteID"),
+    function("EntrySiteName"),
+    function("EntrySiteURL"),
+    function("EntryStatus"),
+    function("EntryTitle"),
+    function("EntryTrackbackCount"),
+    function("EntryTrackbackData"),
+    function("EntryTrackbackID"),
+    function("EntryTrackbackLink"),
+    function("ErrorMessage"),
+    function("FeedbackScore"),
+    function("FeedEntryLink"),
+    function("FeedEntryTitle"),
+    function("FeedInclude"),
+    function("FeedLink"),
+    function("FeedTitle"),
+    function("FileCustomFieldDescription").commercial_pack(),
+    function("FileCustomFieldName").commercial_pack(),
+    function("FileCustomFieldValue").commercial_pack(),
+    function("FileTemplate"),
+    function("FolderBasename"),
+    function("FolderCount"),
+    function("FolderCustomFieldDescription").commercial_pack(),
+    function("FolderCustomFieldName").commercial_pack(),
+    function("FolderCustomFieldValue").commercial_pack(),
+    function("FolderDescription"),
+    function("FolderID"),
+    function("FolderLabel"),
+    function("FolderPath"),
+    function("GetVar").attributes(VAR_ATTRIBUTES),
+    function("HTTPContentType"),
+    function("IfEntryRecommended"),
+    function("ImageCustomFieldDescription").commercial_pack(),
+    function("ImageCustomFieldName").commercial_pack(),
+    function("ImageCustomFieldValue").commercial_pack(),
+    function("ImageHeight"),
+    function("ImageURL"),
+    function("ImageWidth"),
+    // Other attributes of `Include` are passed to the template as variables.
+    function("Include"),
+    function("IndexBasename"),
+    function("IndexLink"),
+    function("IndexName"),
+    function("Link"),
+    function("MaxResults"),
+    function("NextLink"),
+    function("NotifyScript"),
+    function("OtherProfileVar"),
+    function("PageAuthorDisplayName"),
+    function("PageAuthorEmail"),
+    function("PageAuthorLink"),
+    function("PageAuthorURL"),
+    function("PageBasename"),
+    function("PageBody"),
+    function("PageCustomFieldDescription").commercial_pack(),
+    function("PageCustomFieldName").commercial_pack(),
+    function("PageCustomFieldValue").commercial_pack(),
+    function("PageDate").attributes(DATE_ATTRIBUTES),
+    function("PageExcerpt"),
+    function("PageID"),
+    function("PageKeywords"),
+    function("PageModifiedAuthorDisplayName"),
+    function("PageModifiedAuthorEmail"),
+    function("PageModifiedAuthorLink"),
+    function("PageModifiedAuthorURL"),
+    function("PageModifiedDate").attributes(DATE_ATTRIBUTES),
+    function("PageMore"),
+    function("PagePermalink"),
+    function("PagerLink"),
+    function("PageTitle"),
+    function("PasswordValidation"),
+    function("PasswordValidationRule"),
+    function("PingBlogName"),
+    function("PingDate").attributes(DATE_ATTRIBUTES),
+    function("PingExcerpt"),
+    function("PingID"),
+    function("PingIP"),
+    function("PingRank"),
+    function("PingScore"),
+    function("PingScoreAvg"),
+    function("PingScoreCount"),
+    function("PingScoreHigh"),
+    function("PingScoreLow"),
+    function("PingsSentURL"),
+    function("PingTitle"),
+    function("PingURL"),
+    function("PreviousLink"),
+    function("ProductName"),
+    function("PublishCharset"),
+    function("RemoteSignInLink").removed_in(MtVersion::Mt7),
+    function("RemoteSignOutLink").removed_in(MtVersion::Mt7),
+    function("ScoreDate").attributes(DATE_ATTRIBUTES),
+    function("SearchIncludeBlogs"),
+    function("SearchMaxResults"),
+    function("SearchResultCount"),
+    function("SearchResultsFooter"),
+    function("SearchResultsHeader"),
+    function("SearchScript"),
+    function("SearchString"),
+    function("SearchTemplateBlogID"),
+    function("SearchTemplateID"),
+    function("SetVar")
+        .attributes(SET_VAR_ATTRIBUTES)
+        .required(&["name"]),
+    function("SignInLink"),
+    function("SignOnURL"),
+    function("SignOutLink"),
+    function("SiteArchiveURL").since(MtVersion::Mt7),
+    function("SiteCategoryCount").since(MtVersion::Mt7),
+    function("SiteCCLicenseImage").since(MtVersion::Mt7),
+    function("SiteCCLicenseURL").since(MtVersion::Mt7),
+    function("SiteCommentCount").since(MtVersion::Mt7),
+    function("SiteContentCount").since(MtVersion::Mt7),
+    function("SiteDateLanguage").since(MtVersion::Mt7),
+    function("SiteDescription").since(MtVersion::Mt7),
+    function("SiteEntryCount").since(MtVersion::Mt7),
+    function("SiteFileExtension").since(MtVersion::Mt7),
+    function("SiteHost").since(MtVersion::Mt7),
+    function("SiteID").since(MtVersion::Mt7),
+    function("SiteLanguage").since(MtVersion::Mt7),
+    function("SiteName").since(MtVersion::Mt7),
+    function("SitePageCount").since(MtVersion::Mt7),
+    function("SiteParentSite").since(MtVersion::Mt7),
+    function("SitePath").since(MtVersion::Mt7),
+    function("SitePingCount").since(MtVersion::Mt7),
+    function("SiteRelativeURL").since(MtVersion::Mt7),
+    function("SiteThemeID").since(MtVersion::Mt7),
+    function("SiteTimezone").since(MtVersion::Mt7),
+    function("SiteURL").since(MtVersion::Mt7),
+    function("SmartyPantsVersion"),
+    function("StaticFilePath"),
+    function("StaticWebPath"),
+    function("StatsSnippet"),
+    function("SubCategoryPath"),
+    function("SubCatsRecurse"),
+    function("SubFolderRecurse"),
+    function("SupportDirectoryURL"),
+    function("TagCount"),
+    function("TagID"),
+    function("TagLabel"),
+    function("TagName"),
+    function("TagRank"),
+    function("TagSearchLink"),
+    function("TemplateCreatedOn"),
+    function("TemplateCustomFieldDescription").commercial_pack(),
+    function("TemplateCustomFieldName").commercial_pack(),
+    function("TemplateCustomFieldValue").commercial_pack(),
+    function("TemplateNote"),
+    function("TextileHeadOffset"),
+    function("TextileOptions"),
+    function("TotalPages"),
+    function("TrackbackScript"),
+    function("TypeKeyToken").removed_in(MtVersion::Mt7),
+    function("UserSessionState"),
+    function("Var").attributes(VAR_ATTRIBUTES),
+    function("Version"),
+    function("VideoCustomFieldDescription").commercial_pack(),
+    function("VideoCustomFieldName").commercial_pack(),
+    function("VideoCustomFieldValue").commercial_pack(),
+    function("WebsiteCCLicenseImage"),
+    function("WebsiteCCLicenseURL"),
+    function("WebsiteCommentCount"),
+    function("WebsiteCustomFieldDescription").commercial_pack(),
+    function("WebsiteCustomFieldName").commercial_pack(),
+    function("WebsiteCustomFieldValue").commercial_pack(),
+    function("WebsiteDateLanguage"),
+    function("WebsiteDescription"),
+    function("WebsiteEntryCount"),
+    function("WebsiteFileExtension"),
+    function("WebsiteHost"),
+    function("WebsiteID"),
+    function("WebsiteLanguage"),
+    function("WebsiteName"),
+    function("WebsitePageCount"),
+    function("WebsitePath"),
+    function("WebsitePingCount"),
+    function("WebsiteRelativeURL"),
+    function("WebsiteThemeID"),
+    function("WebsiteTimezone"),
+    function("WebsiteURL"),
+    function("WidgetManager"),
+    function("WidgetSet"),
+    function("XMLRPCScript"),
+    // Block and conditional tags.
+    block("App:Form"),
+    block("App:Listing"),
+    block("App:Setting"),
+    block("App:SettingGroup"),
+    block("App:StatusMsg"),
+    block("App:Widget"),
+    block("ArchiveList"),
+    conditional("ArchiveListFooter"),
+    conditional("ArchiveListHeader"),
+    block("ArchiveNext"),
+    block("ArchivePrevious"),
+    conditional("AssetIfTagged"),
+    conditional("AssetIsFirstInRow"),
+    conditional("AssetIsLastInRow"),
+    block("Assets").attributes(ASSETS_ATTRIBUTES),
+    conditional("AssetsFooter"),
+    conditional("AssetsHeader"),
+    block("AssetTags"),
+    block("AuthorCustomFields").commercial_pack(),
+    conditional("AuthorHasEntry"),
+    conditional("AuthorHasPage"),
+    block("AuthorNext"),
+    block("AuthorPrevious"),
+    block("Authors"),
+    conditional("AuthorsFooter"),
+    conditional("AuthorsHeader"),
+    block("AuthorUserpicAsset"),
+    block("BlogCustomFields").commercial_pack(),
+    conditional("BlogIfCCLicense"),
+    conditional("BlogIfCommentsOpen"),
+    block("BlogParentWebsite"),
+    block("Blogs"),
+    block("Calendar"),
+    conditional("CalendarIfBlank"),
+    conditional("CalendarIfEntries"),
+    conditional("CalendarIfNoEntries"),
+    conditional("CalendarIfToday"),
+    conditional("CalendarWeekFooter"),
+    conditional("CalendarWeekHeader"),
+    block("Categories").attributes(CATEGORIES_ATTRIBUTES),
+    conditional("CategoriesFooter"),
+    conditional("CategoriesHeader"),
+    block("CategoryCustomFields").commercial_pack(),
+    conditional("CategoryIfAllowPings"),
+    block("CategoryNext"),
+    block("CategoryPrevious"),
+    block("CategorySets").since(MtVersion::Mt7),
+    block("CommentCustomFields").commercial_pack(),
+    block("CommentEntry"),
+    conditional("CommenterIfTrusted"),
+    block("CommenterUserpicAsset"),
+    conditional("CommentIfModerated"),
+    block("CommentParent"),
+    block("CommentReplies"),
+    block("Comments"),
+    conditional("CommentsFooter"),
+    conditional("CommentsHeader"),
+    block("ContentAuthorUserpicAsset").since(MtVersion::Mt7),
+    block("ContentCalendar").since(MtVersion::Mt7),
+    block("ContentField").since(MtVersion::Mt7),
+    conditional("ContentFieldFooter").since(MtVersion::Mt7),
+    conditional("ContentFieldHeader").since(MtVersion::Mt7),
+    block("ContentFields").since(MtVersion::Mt7),
+    block("ContentNext").since(MtVersion::Mt7),
+    block("ContentPrevious").since(MtVersion::Mt7),
+    block("Contents").since(MtVersion::Mt7),
+    conditional("ContentsFooter").since(MtVersion::Mt7),
+    conditional("ContentsHeader").since(MtVersion::Mt7),
+    block("ContentType").since(MtVersion::Mt7),
+    block("ContentTypes").since(MtVersion::Mt7),
+    conditional("CustomFieldIsRequired").commercial_pack(),
+    block("CustomFields").commercial_pack(),
+    conditional("DateFooter"),
+    conditional("DateHeader"),
+    block("Entries").attributes(ENTRIES_ATTRIBUTES),
+    conditional("EntriesFooter"),
+    conditional("EntriesHeader"),
+    block("EntryAdditionalCategories"),
+    block("EntryAssets"),
+    block("EntryAuthorUserpicAsset"),
+    block("EntryCategories"),
+    block("EntryCustomFields").commercial_pack(),
+    conditional("EntryIfAllowComments"),
+    conditional("EntryIfAllowPings"),
+    conditional("EntryIfCategory"),
+    conditional("EntryIfCommentsOpen"),
+    conditional("EntryIfExtended"),
+    conditional("EntryIfTagged"),
+    block("EntryNext"),
+    block("EntryPrevious"),
+    block("EntryTags"),
+    block("FolderCustomFields").commercial_pack(),
+    conditional("FolderFooter"),
+    conditional("FolderHeader"),
+    block("FolderNext"),
+    block("FolderPrevious"),
+    block("Folders"),
+    block("For").attributes(&[
+        "var",
+        "from",
+        "start",
+        "to",
+        "end",
+        "step",
+        "increment",
+        "glue",
+    ]),
+    conditional("HasNoParentCategory"),
+    conditional("HasNoSubCategories"),
+    conditional("HasParentCategory"),
+    conditional("HasParentFolder"),
+    conditional("HasSubCategories"),
+    conditional("HasSubFolders"),
+    conditional("If").attributes(IF_ATTRIBUTES),
+    conditional("IfAllowCommentHTML"),
+    conditional("IfArchiveType").attributes(&["type", "archive_type"]),
+    conditional("IfArchiveTypeEnabled"),
+    conditional("IfAuthor"),
+    conditional("IfBlog"),
+    conditional("IfCategory"),
+    conditional("IfCommenterIsAuthor"),
+    conditional("IfCommenterIsEntryAuthor"),
+    conditional("IfCommenterRegistrationAllowed"),
+    conditional("IfCommenterTrusted"),
+    conditional("IfCommentParent"),
+    conditional("IfCommentReplies"),
+    conditional("IfCommentsAccepted"),
+    conditional("IfCommentsActive"),
+    conditional("IfCommentsAllowed"),
+    conditional("IfCommentsModerated"),
+    conditional("IfCurrentPage"),
+    conditional("IfDynamic"),
+    conditional("IfExternalUserManagement"),
+    conditional("IfFolder"),
+    conditional("IfImageSupport"),
+    conditional("IfIsAncestor"),
+    conditional("IfIsDescendant"),
+    conditional("IfMaxResultsCutoff"),
+    conditional("IfMoreResults"),
+    conditional("IfNeedEmail"),
+    conditional("IfNonEmpty").attributes(&["tag", "name"]),
+    conditional("IfNonZero").attributes(&["tag", "name"]),
+    conditional("IfPingsAccepted"),
+    conditional("IfPingsActive"),
+    conditional("IfPingsAllowed"),
+    conditional("IfPingsModerated"),
+    conditional("IfPreviousResults"),
+    conditional("IfRegistrationAllowed"),
+    conditional("IfRegistrationNotRequired"),
+    conditional("IfRegistrationRequired"),
+    conditional("IfRequireCommentEmails"),
+    conditional("IfStatic"),
+    conditional("IfStraightSearch"),
+    conditional("IfTagSearch"),
+    conditional("IfTypeKeyToken").removed_in(MtVersion::Mt7),
+    conditional("IfWebsite"),
+    block("Ignore").attributes(&[]),
+    block("IncludeBlock"),
+    block("IndexList"),
+    block("Loop")
+        .attributes(&["name", "function", "sort_by", "glue"])
+        .required(&["name"]),
+    block("MultiBlog"),
+    conditional("MultiBlogIfLocalBlog"),
+    block("MultiBlogLocalBlog"),
+    block("NoSearch"),
+    block("NoSearchResults"),
+    block("OtherBlog"),
+    block("PageAssets"),
+    block("PageCustomFields").commercial_pack(),
+    block("PageFolder"),
+    block("PageNext"),
+    block("PagePrevious"),
+    block("PagerBlock"),
+    block("Pages").attributes(PAGES_ATTRIBUTES),
+    conditional("PagesFooter"),
+    conditional("PagesHeader"),
+    block("PageTags"),
+    block("ParentCategories"),
+    block("ParentCategory"),
+    block("ParentFolder"),
+    block("ParentFolders"),
+    block("PingEntry"),
+    block("Pings"),
+    conditional("PingsFooter"),
+    conditional("PingsHeader"),
+    block("PingsSent"),
+    conditional("PingsSentFooter"),
+    conditional("PingsSentHeader"),
+    block("SearchResults"),
+    block("Section"),
+    block("SetHashVar")
+        .attributes(&["name"])
+        .required(&["name"]),
+    block("SetVarBlock")
+        .attributes(SET_VAR_ATTRIBUTES)
+        .required(&["name"]),
+    block("SetVars").attributes(&[]),
+    block("SetVarTemplate")
+        .attributes(&["name"])
+        .required(&["name"]),
+    conditional("SiteHasChildSite").since(MtVersion::Mt7),
+    conditional("SiteIfCCLicense").since(MtVersion::Mt7),
+    block("Sites"),
+    block("SubCategories"),
+    conditional("SubCatIsFirst"),
+    conditional("SubCatIsLast"),
+    conditional("SubFolderIsFirst"),
+    conditional("SubFolderIsLast"),
+    block("SubFolders"),
+    block("Tags"),
+    block("TemplateCustomFields").commercial_pack(),
+    block("TopLevelCategories"),
+    block("TopLevelFolder"),
+    block("TopLevelFolders"),
+    block("TopLevelParent"),
+    conditional("Unless").attributes(IF_ATTRIBUTES),
+    block("Website"),
+    block("WebsiteCustomFields").commercial_pack(),
+    conditional("WebsiteHasBlog"),
+    conditional("WebsiteIfCCLicense"),
+    block("Websites"),
+    conditional("WidgetSetExists"),
+];
+
+static MODIFIERS: &[ModifierDefinition] = &[
+    modifier("capitalize", 1),
+    modifier("cat", 1),
+    modifier("count_characters", 1),
+    modifier("count_paragraphs", 1),
+    modifier("count_words", 1),
+    modifier("decode_html", 1),
+    modifier("decode_xml", 1),
+    modifier("dirify", 1),
+    modifier("encode_html", 1),
+    modifier("encode_js", 1),
+    modifier("encode_php", 1),
+    modifier("encode_sha1", 1),
+    modifier("encode_url", 1),
+    modifier("encode_xml", 1),
+    modifier("escape", 1),
+    modifier("filters", 1),
+    modifier("from_json", 1),
+    modifier("indent", 1),
+    modifier("lower_case", 1),
+    modifier("ltrim", 1),
+    modifier("mteval", 1),
+    modifier("nl2br", 1),
+    modifier("nofollowfy", 1),
+    modifier("numify", 1),
+    modifier("regex_replace", 2),
+    modifier("remove_html", 1),
+    modifier("replace", 2),
+    modifier("rtrim", 1),
+    modifier("sanitize", 1),
+    modifier("setvar", 1),
+    modifier("space_pad", 1),
+    modifier("spacify", 1),
+    modifier("sprintf", 1),
+    modifier("strip", 1),
+    modifier("strip_linefeeds", 1),
+    modifier("strip_tags", 1),
+    modifier("to_json", 1),
+    modifier("trim", 1),
+    modifier("trim_to", 1),
+    modifier("upper_case", 1),
+    modifier("words", 1),
+    modifier("wrap_text", 1),
+    modifier("zero_pad", 1),
+];
+
+/// Catalogue of known tags and global modifiers.
+///
+/// Names are looked up case-insensitively and without prefix.
+///
+/// # Examples
+///
+/// ```
+/// use mtml_parser::tag::{TagKind, CATALOG};
+///
+/// assert_eq!(CATALOG.tag("entries").unwrap().kind, TagKind::Block);
+/// assert_eq!(CATALOG.modifier("regex_replace").unwrap().arity, 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TagCatalog {
+    tags: HashMap<String, TagDefinition>,
+    modifiers: HashMap<String, ModifierDefinition>,
+}
+
+impl TagCatalog {
+    /// Create a catalogue of the tags of every supported version and edition,
+    /// and of the global modifiers.
+    pub fn new() -> Self {
+        return TagCatalog {
+            tags: TAGS
+                .iter()
+                .map(|tag| (tag.name.to_lowercase(), *tag))
+                .collect(),
+            modifiers: MODIFIERS
+                .iter()
+                .map(|modifier| (modifier.name.to_lowercase(), *modifier))
+                .collect(),
+        };
+    }
+
+    /// Create a catalogue of the tags available in a version and edition.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mtml_parser::tag::{Edition, MtVersion, TagCatalog};
+    ///
+    /// let catalog = TagCatalog::for_version(MtVersion::Mt6, Edition::Standard);
+    /// assert!(catalog.tag("Entries").is_some());
+    /// assert!(catalog.tag("Contents").is_none());
+    /// ```
+    pub fn for_version(version: MtVersion, edition: Edition) -> Self {
+        let mut catalog = TagCatalog::new();
+        catalog
+            .tags
+            .retain(|_, tag| tag.is_available_in(version, edition));
+        return catalog;
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&TagDefinition> {
+        return self.tags.get(&name.to_lowercase());
+    }
+
+    pub fn modifier(&self, name: &str) -> Option<&ModifierDefinition> {
+        return self.modifiers.get(&name.to_lowercase());
+    }
+
+    /// Iterate over all tags, in no particular order.
+    pub fn tags(&self) -> impl Iterator<Item = &TagDefinition> {
+        return self.tags.values();
+    }
+
+    /// Iterate over all global modifiers, in no particular order.
+    pub fn modifiers(&self) -> impl Iterator<Item = &ModifierDefinition> {
+        return self.modifiers.values();
+    }
+
+    /// Return whether `attribute` may be used on `tag`, either as one of its
+    /// own attributes or as a global modifier.
+    ///
+    /// Unknown tags accept any attribute.
+    pub fn accepts_attribute(&self, tag: &str, attribute: &str) -> bool {
+        return self.modifier(attribute).is_some()
+            || self
+                .tag(tag)
+                .is_none_or(|tag| tag.accepts_attribute(attribute));
+    }
+}
+
+impl Default for TagCatalog {
+    fn default() -> Self {
+        return TagCatalog::new();
+    }
+}
+
+pub static CATALOG: Lazy<TagCatalog> = Lazy::new(TagCatalog::new);
+
+/// Return whether a tag name follows the naming convention of conditional
+/// tags: `If`, `Unless`, a name containing the word `If`, `Has` or `Is`, or
+/// ending in `Exists`, such as `IfNonEmpty`, `EntryIfTagged`,
+/// `HasSubCategories`, `SubCatIsFirst` or `WidgetSetExists`.
+///
+/// Words are split at uppercase letters. A name written in lowercase only has
+/// no word boundaries, so it only matches if it is `if` or `unless`; this
+/// keeps tags such as `iframe` or `hashtags` from being taken for conditionals.
+pub fn looks_conditional(name: &str) -> bool {
+    const WORDS: &[&str] = &["if", "unless", "has", "is"];
+    let bounds = [0]
+        .into_iter()
+        .chain(
+            name.char_indices()
+                .filter(|(_, c)| c.is_uppercase())
+                .map(|(i, _)| i),
+        )
+        .chain([name.len()])
+        .collect::<Vec<_>>();
+    let words = bounds
+        .windows(2)
+        .map(|w| &name[w[0]..w[1]])
+        .collect::<Vec<_>>();
+    return name.eq_ignore_ascii_case("if")
+        || name.eq_ignore_ascii_case("unless")
+        || words
+            .iter()
+            .any(|w| WORDS.iter().any(|word| w.eq_ignore_ascii_case(word)))
+        || words
+            .last()
+            .is_some_and(|w| w.eq_ignore_ascii_case("exists"));
+}
 
 pub static FUNCTION_TAGS: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| {
-    let mut m = HashSet::new();
-
-    m.insert("admincgipath".to_string());
-    m.insert("adminscript".to_string());
-    m.insert("app:actionbar".to_string());
-    m.insert("app:link".to_string());
-    m.insert("app:listfilters".to_string());
-    m.insert("app:pageactions".to_string());
-    m.insert("archivecategory".to_string());
-    m.insert("archivecount".to_string());
-    m.insert("archivedate".to_string());
-    m.insert("archivedateend".to_string());
-    m.insert("archivefile".to_string());
-    m.insert("archivelabel".to_string());
-    m.insert("archivelink".to_string());
-    m.insert("archivetitle".to_string());
-    m.insert("archivetype".to_string());
-    m.insert("archivetypelabel".to_string());
-    m.insert("assetaddedby".to_string());
-    m.insert("assetblogid".to_string());
-    m.insert("assetcount".to_string());
-    m.insert("assetdateadded".to_string());
-    m.insert("assetdescription".to_string());
-    m.insert("assetfileext".to_string());
-    m.insert("assetfilename".to_string());
-    m.insert("assetfilepath".to_string());
-    m.insert("assetid".to_string());
-    m.insert("assetlabel".to_string());
-    m.insert("assetlink".to_string());
-    m.insert("assetmimetype".to_string());
-    m.insert("assetproperty".to_string());
-    m.insert("assetrank".to_string());
-    m.insert("assetscore".to_string());
-    m.insert("assetscoreavg".to_string());
-    m.insert("assetscorecount".to_string());
-    m.insert("assetscorehigh".to_string());
-    m.insert("assetscorelow".to_string());
-    m.insert("assetsiteid".to_string());
-    m.insert("assetthumbnaillink".to_string());
-    m.insert("assetthumbnailurl".to_string());
-    m.insert("assettype".to_string());
-    m.insert("asseturl".to_string());
-    m.insert("atomscript".to_string());
-    m.insert("audiocustomfielddescription".to_string());
-    m.insert("audiocustomfieldname".to_string());
-    m.insert("audiocustomfieldvalue".to_string());
-    m.insert("authorauthiconurl".to_string());
-    m.insert("authorauthtype".to_string());
-    m.insert("authorbasename".to_string());
-    m.insert("authorcommentcount".to_string());
-    m.insert("authorcontentcount".to_string());
-    m.insert("authorcustomfielddescription".to_string());
-    m.insert("authorcustomfieldname".to_string());
-    m.insert("authorcustomfieldvalue".to_string());
-    m.insert("authordisplayname".to_string());
-    m.insert("authoremail".to_string());
-    m.insert("authorentriescount".to_string());
-    m.insert("authorentrycount".to_string());
-    m.insert("authorfavoriteentries".to_string());
-    m.insert("authorfollowerscount".to_string());
-    m.insert("authorfollowingcount".to_string());
-    m.insert("authorfollowlink".to_string());
-    m.insert("authorid".to_string());
-    m.insert("authorname".to_string());
-    m.insert("authorrank".to_string());
-    m.insert("authorscore".to_string());
-    m.insert("authorscoreavg".to_string());
-    m.insert("authorscorecount".to_string());
-    m.insert("authorscorehigh".to_string());
-    m.insert("authorscorelow".to_string());
-    m.insert("authorunfollowlink".to_string());
-    m.insert("authorurl".to_string());
-    m.insert("authoruserpic".to_string());
-    m.insert("authoruserpicurl".to_string());
-    m.insert("blockeditorblockasset".to_string());
-    m.insert("blockeditorblocks".to_string());
-    m.insert("blogarchiveurl".to_string());
-    m.insert("blogcategorycount".to_string());
-    m.insert("blogcclicenseimage".to_string());
-    m.insert("blogcclicenseurl".to_string());
-    m.insert("blogcommentcount".to_string());
-    m.insert("blogcustomfielddescription".to_string());
-    m.insert("blogcustomfieldname".to_string());
-    m.insert("blogcustomfieldvalue".to_string());
-    m.insert("blogdatelanguage".to_string());
-    m.insert("blogdescription".to_string());
-    m.insert("blogentrycount".to_string());
-    m.insert("blogfileextension".to_string());
-    m.insert("bloghost".to_string());
-    m.insert("blogid".to_string());
-    m.insert("bloglanguage".to_string());
-    m.insert("blogname".to_string());
-    m.insert("blogpagecount".to_string());
-    m.insert("blogpingcount".to_string());
-    m.insert("blogrelativeurl".to_string());
-    m.insert("blogsitepath".to_string());
-    m.insert("blogtemplatesetid".to_string());
-    m.insert("blogthemeid".to_string());
-    m.insert("blogtimezone".to_string());
-    m.insert("blogurl".to_string());
-    m.insert("buildtemplateid".to_string());
-    m.insert("calendarcellnumber".to_string());
-    m.insert("calendardate".to_string());
-    m.insert("calendarday".to_string());
-    m.insert("canonicallink".to_string());
-    m.insert("canonicalurl".to_string());
-    m.insert("captchafields".to_string());
-    m.insert("categoryarchivelink".to_string());
-    m.insert("categorybasename".to_string());
-    m.insert("categorycommentcount".to_string());
-    m.insert("categorycount".to_string());
-    m.insert("categorycustomfielddescription".to_string());
-    m.insert("categorycustomfieldname".to_string());
-    m.insert("categorycustomfieldvalue".to_string());
-    m.insert("categorydescription".to_string());
-    m.insert("categoryid".to_string());
-    m.insert("categorylabel".to_string());
-    m.insert("categorysetname".to_string());
-    m.insert("categorytrackbackcount".to_string());
-    m.insert("categorytrackbacklink".to_string());
-    m.insert("cclicenserdf".to_string());
-    m.insert("cgihost".to_string());
-    m.insert("cgipath".to_string());
-    m.insert("cgirelativeurl".to_string());
-    m.insert("cgiserverpath".to_string());
-    m.insert("commentauthor".to_string());
-    m.insert("commentauthoridentity".to_string());
-    m.insert("commentauthorlink".to_string());
-    m.insert("commentblogid".to_string());
-    m.insert("commentbody".to_string());
-    m.insert("commentcustomfielddescription".to_string());
-    m.insert("commentcustomfieldname".to_string());
-    m.insert("commentcustomfieldvalue".to_string());
-    m.insert("commentdate".to_string());
-    m.insert("commentemail".to_string());
-    m.insert("commententryid".to_string());
-    m.insert("commenterauthiconurl".to_string());
-    m.insert("commenterauthtype".to_string());
-    m.insert("commenteremail".to_string());
-    m.insert("commenterid".to_string());
-    m.insert("commentername".to_string());
-    m.insert("commenternamethunk".to_string());
-    m.insert("commenterurl".to_string());
-    m.insert("commenterusername".to_string());
-    m.insert("commenteruserpic".to_string());
-    m.insert("commenteruserpicurl".to_string());
-    m.insert("commentid".to_string());
-    m.insert("commentip".to_string());
-    m.insert("commentlink".to_string());
-    m.insert("commentname".to_string());
-    m.insert("commentordernumber".to_string());
-    m.insert("commentparentid".to_string());
-    m.insert("commentpreviewauthor".to_string());
-    m.insert("commentpreviewauthorlink".to_string());
-    m.insert("commentpreviewbody".to_string());
-    m.insert("commentpreviewdate".to_string());
-    m.insert("commentpreviewemail".to_string());
-    m.insert("commentpreviewip".to_string());
-    m.insert("commentpreviewisstatic".to_string());
-    m.insert("commentpreviewurl".to_string());
-    m.insert("commentrank".to_string());
-    m.insert("commentrepliesrecurse".to_string());
-    m.insert("commentreplytolink".to_string());
-    m.insert("commentscore".to_string());
-    m.insert("commentscoreavg".to_string());
-    m.insert("commentscorecount".to_string());
-    m.insert("commentscorehigh".to_string());
-    m.insert("commentscorelow".to_string());
-    m.insert("commentscript".to_string());
-    m.insert("commentsiteid".to_string());
-    m.insert("commenturl".to_string());
-    m.insert("communityscript".to_string());
-    m.insert("configfile".to_string());
-    m.insert("contentauthordisplayname".to_string());
-    m.insert("contentauthoremail".to_string());
-    m.insert("contentauthorid".to_string());
-    m.insert("contentauthorlink".to_string());
-    m.insert("contentauthorurl".to_string());
-    m.insert("contentauthorusername".to_string());
-    m.insert("contentauthoruserpic".to_string());
-    m.insert("contentauthoruserpicurl".to_string());
-    m.insert("contentcreateddate".to_string());
-    m.insert("contentdatasearchscript".to_string());
-    m.insert("contentdate".to_string());
-    m.insert("contentfieldvalue".to_string());
-    m.insert("contentid".to_string());
-    m.insert("contentidentifier".to_string());
-    m.insert("contentlabel".to_string());
-    m.insert("contentmodifiedauthordisplayname".to_string());
-    m.insert("contentmodifiedauthoremail".to_string());
-    m.insert("contentmodifiedauthorid".to_string());
-    m.insert("contentmodifiedauthorlink".to_string());
-    m.insert("contentmodifiedauthorurl".to_string());
-    m.insert("contentmodifiedauthorusername".to_string());
-    m.insert("contentmodifiedauthoruserpic".to_string());
-    m.insert("contentmodifiedauthoruserpicasset".to_string());
-    m.insert("contentmodifiedauthoruserpicurl".to_string());
-    m.insert("contentmodifieddate".to_string());
-    m.insert("contentpermalink".to_string());
-    m.insert("contentscount".to_string());
-    m.insert("contentsitedescription".to_string());
-    m.insert("contentsiteid".to_string());
-    m.insert("contentsitename".to_string());
-    m.insert("contentsiteurl".to_string());
-    m.insert("contentstatus".to_string());
-    m.insert("contenttypedescription".to_string());
-    m.insert("contenttypeid".to_string());
-    m.insert("contenttypename".to_string());
-    m.insert("contenttypeuniqueid".to_string());
-    m.insert("contentuniqueid".to_string());
-    m.insert("contentunpublisheddate".to_string());
-    m.insert("currentpage".to_string());
-    m.insert("customfieldbasename".to_string());
-    m.insert("customfielddescription".to_string());
-    m.insert("customfieldhtml".to_string());
-    m.insert("customfieldname".to_string());
-    m.insert("customfieldvalue".to_string());
-    m.insert("dataapiscript".to_string());
-    m.insert("dataapiversion".to_string());
-    m.insert("date".to_string());
-    m.insert("defaultlanguage".to_string());
-    m.insert("entriescount".to_string());
-    m.insert("entryatomid".to_string());
-    m.insert("entryauthor".to_string());
-    m.insert("entryauthordisplayname".to_string());
-    m.insert("entryauthoremail".to_string());
-    m.insert("entryauthorid".to_string());
-    m.insert("entryauthorlink".to_string());
-    m.insert("entryauthornickname".to_string());
-    m.insert("entryauthorurl".to_string());
-    m.insert("entryauthorusername".to_string());
-    m.insert("entryauthoruserpic".to_string());
-    m.insert("entryauthoruserpicurl".to_string());
-    m.insert("entrybasename".to_string());
-    m.insert("entryblogdescription".to_string());
-    m.insert("entryblogid".to_string());
-    m.insert("entryblogname".to_string());
-    m.insert("entryblogurl".to_string());
-    m.insert("entrybody".to_string());
-    m.insert("entrycategory".to_string());
-    m.insert("entryclass".to_string());
-    m.insert("entryclasslabel".to_string());
-    m.insert("entrycommentcount".to_string());
-    m.insert("entrycreateddate".to_string());
-    m.insert("entrycustomfielddescription".to_string());
-    m.insert("entrycustomfieldname".to_string());
-    m.insert("entrycustomfieldvalue".to_string());
-    m.insert("entrydate".to_string());
-    m.insert("entryeditlink".to_string());
-    m.insert("entryexcerpt".to_string());
-    m.insert("entryflag".to_string());
-    m.insert("entryid".to_string());
-    m.insert("entrykeywords".to_string());
-    m.insert("entrylink".to_string());
-    m.insert("entrymodifiedauthordisplayname".to_string());
-    m.insert("entrymodifiedauthoremail".to_string());
-    m.insert("entrymodifiedauthorid".to_string());
-    m.insert("entrymodifiedauthorlink".to_string());
-    m.insert("entrymodifiedauthorurl".to_string());
-    m.insert("entrymodifiedauthorusername".to_string());
-    m.insert("entrymodifiedauthoruserpic".to_string());
-    m.insert("entrymodifiedauthoruserpicasset".to_string());
-    m.insert("entrymodifiedauthoruserpicurl".to_string());
-    m.insert("entrymodifieddate".to_string());
-    m.insert("entrymore".to_string());
-    m.insert("entrypermalink".to_string());
-    m.insert("entryrank".to_string());
-    m.insert("entryrecommendedtotal".to_string());
-    m.insert("entryrecommendvotelink".to_string());
-    m.insert("entryscore".to_string());
-    m.insert("entryscoreavg".to_string());
-    m.insert("entryscorecount".to_string());
-    m.insert("entryscorehigh".to_string());
-    m.insert("entryscorelow".to_string());
-    m.insert("entrysitedescription".to_string());
-    m.insert("entrysiteid".to_string());
-    m.insert("entrysitename".to_string());
-    m.insert("entrysiteurl".to_string());
-    m.insert("entrystatus".to_string());
-    m.insert("entrytitle".to_string());
-    m.insert("entrytrackbackcount".to_string());
-    m.insert("entrytrackbackdata".to_string());
-    m.insert("entrytrackbackid".to_string());
-    m.insert("entrytrackbacklink".to_string());
-    m.insert("errormessage".to_string());
-    m.insert("feedbackscore".to_string());
-    m.insert("feedentrylink".to_string());
-    m.insert("feedentrytitle".to_string());
-    m.insert("feedinclude".to_string());
-    m.insert("feedlink".to_string());
-    m.insert("feedtitle".to_string());
-    m.insert("filecustomfielddescription".to_string());
-    m.insert("filecustomfieldname".to_string());
-    m.insert("filecustomfieldvalue".to_string());
-    m.insert("filetemplate".to_string());
-    m.insert("folderbasename".to_string());
-    m.insert("foldercount".to_string());
-    m.insert("foldercustomfielddescription".to_string());
-    m.insert("foldercustomfieldname".to_string());
-    m.insert("foldercustomfieldvalue".to_string());
-    m.insert("folderdescription".to_string());
-    m.insert("folderid".to_string());
-    m.insert("folderlabel".to_string());
-    m.insert("folderpath".to_string());
-    m.insert("getvar".to_string());
-    m.insert("httpcontenttype".to_string());
-    m.insert("ifentryrecommended".to_string());
-    m.insert("imagecustomfielddescription".to_string());
-    m.insert("imagecustomfieldname".to_string());
-    m.insert("imagecustomfieldvalue".to_string());
-    m.insert("imageheight".to_string());
-    m.insert("imageurl".to_string());
-    m.insert("imagewidth".to_string());
-    m.insert("include".to_string());
-    m.insert("indexbasename".to_string());
-    m.insert("indexlink".to_string());
-    m.insert("indexname".to_string());
-    m.insert("link".to_string());
-    m.insert("maxresults".to_string());
-    m.insert("nextlink".to_string());
-    m.insert("notifyscript".to_string());
-    m.insert("otherprofilevar".to_string());
-    m.insert("pageauthordisplayname".to_string());
-    m.insert("pageauthoremail".to_string());
-    m.insert("pageauthorlink".to_string());
-    m.insert("pageauthorurl".to_string());
-    m.insert("pagebasename".to_string());
-    m.insert("pagebody".to_string());
-    m.insert("pagecustomfielddescription".to_string());
-    m.insert("pagecustomfieldname".to_string());
-    m.insert("pagecustomfieldvalue".to_string());
-    m.insert("pagedate".to_string());
-    m.insert("pageexcerpt".to_string());
-    m.insert("pageid".to_string());
-    m.insert("pagekeywords".to_string());
-    m.insert("pagemodifiedauthordisplayname".to_string());
-    m.insert("pagemodifiedauthoremail".to_string());
-    m.insert("pagemodifiedauthorlink".to_string());
-    m.insert("pagemodifiedauthorurl".to_string());
-    m.insert("pagemodifieddate".to_string());
-    m.insert("pagemore".to_string());
-    m.insert("pagepermalink".to_string());
-    m.insert("pagerlink".to_string());
-    m.insert("pagetitle".to_string());
-    m.insert("passwordvalidation".to_string());
-    m.insert("passwordvalidationrule".to_string());
-    m.insert("pingblogname".to_string());
-    m.insert("pingdate".to_string());
-    m.insert("pingexcerpt".to_string());
-    m.insert("pingid".to_string());
-    m.insert("pingip".to_string());
-    m.insert("pingrank".to_string());
-    m.insert("pingscore".to_string());
-    m.insert("pingscoreavg".to_string());
-    m.insert("pingscorecount".to_string());
-    m.insert("pingscorehigh".to_string());
-    m.insert("pingscorelow".to_string());
-    m.insert("pingssenturl".to_string());
-    m.insert("pingtitle".to_string());
-    m.insert("pingurl".to_string());
-    m.insert("previouslink".to_string());
-    m.insert("productname".to_string());
-    m.insert("publishcharset".to_string());
-    m.insert("remotesigninlink".to_string());
-    m.insert("remotesignoutlink".to_string());
-    m.insert("scoredate".to_string());
-    m.insert("searchincludeblogs".to_string());
-    m.insert("searchmaxresults".to_string());
-    m.insert("searchresultcount".to_string());
-    m.insert("searchresultsfooter".to_string());
-    m.insert("searchresultsheader".to_string());
-    m.insert("searchscript".to_string());
-    m.insert("searchstring".to_string());
-    m.insert("searchtemplateblogid".to_string());
-    m.insert("searchtemplateid".to_string());
-    m.insert("setvar".to_string());
-    m.insert("signinlink".to_string());
-    m.insert("signonurl".to_string());
-    m.insert("signoutlink".to_string());
-    m.insert("sitearchiveurl".to_string());
-    m.insert("sitecategorycount".to_string());
-    m.insert("sitecclicenseimage".to_string());
-    m.insert("sitecclicenseurl".to_string());
-    m.insert("sitecommentcount".to_string());
-    m.insert("sitecontentcount".to_string());
-    m.insert("sitedatelanguage".to_string());
-    m.insert("sitedescription".to_string());
-    m.insert("siteentrycount".to_string());
-    m.insert("sitefileextension".to_string());
-    m.insert("sitehost".to_string());
-    m.insert("siteid".to_string());
-    m.insert("sitelanguage".to_string());
-    m.insert("sitename".to_string());
-    m.insert("sitepagecount".to_string());
-    m.insert("siteparentsite".to_string());
-    m.insert("sitepath".to_string());
-    m.insert("sitepingcount".to_string());
-    m.insert("siterelativeurl".to_string());
-    m.insert("sitethemeid".to_string());
-    m.insert("sitetimezone".to_string());
-    m.insert("siteurl".to_string());
-    m.insert("smartypantsversion".to_string());
-    m.insert("staticfilepath".to_string());
-    m.insert("staticwebpath".to_string());
-    m.insert("statssnippet".to_string());
-    m.insert("subcategorypath".to_string());
-    m.insert("subcatsrecurse".to_string());
-    m.insert("subfolderrecurse".to_string());
-    m.insert("supportdirectoryurl".to_string());
-    m.insert("tagcount".to_string());
-    m.insert("tagid".to_string());
-    m.insert("taglabel".to_string());
-    m.insert("tagname".to_string());
-    m.insert("tagrank".to_string());
-    m.insert("tagsearchlink".to_string());
-    m.insert("templatecreatedon".to_string());
-    m.insert("templatecustomfielddescription".to_string());
-    m.insert("templatecustomfieldname".to_string());
-    m.insert("templatecustomfieldvalue".to_string());
-    m.insert("templatenote".to_string());
-    m.insert("textileheadoffset".to_string());
-    m.insert("textileoptions".to_string());
-    m.insert("totalpages".to_string());
-    m.insert("trackbackscript".to_string());
-    m.insert("typekeytoken".to_string());
-    m.insert("usersessionstate".to_string());
-    m.insert("var".to_string());
-    m.insert("version".to_string());
-    m.insert("videocustomfielddescription".to_string());
-    m.insert("videocustomfieldname".to_string());
-    m.insert("videocustomfieldvalue".to_string());
-    m.insert("websitecclicenseimage".to_string());
-    m.insert("websitecclicenseurl".to_string());
-    m.insert("websitecommentcount".to_string());
-    m.insert("websitecustomfielddescription".to_string());
-    m.insert("websitecustomfieldname".to_string());
-    m.insert("websitecustomfieldvalue".to_string());
-    m.insert("websitedatelanguage".to_string());
-    m.insert("websitedescription".to_string());
-    m.insert("websiteentrycount".to_string());
-    m.insert("websitefileextension".to_string());
-    m.insert("websitehost".to_string());
-    m.insert("websiteid".to_string());
-    m.insert("websitelanguage".to_string());
-    m.insert("websitename".to_string());
-    m.insert("websitepagecount".to_string());
-    m.insert("websitepath".to_string());
-    m.insert("websitepingcount".to_string());
-    m.insert("websiterelativeurl".to_string());
-    m.insert("websitethemeid".to_string());
-    m.insert("websitetimezone".to_string());
-    m.insert("websiteurl".to_string());
-    m.insert("widgetmanager".to_string());
-    m.insert("widgetset".to_string());
-    m.insert("xmlrpcscript".to_string());
+    let m = CATALOG
+        .tags()
+        .filter(|tag| tag.kind == TagKind::Function)
+        .map(|tag| tag.name.to_lowercase())
+        .collect();
 
     return Mutex::new(m);
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog() {
+        let tag = CATALOG.tag("SETVAR").unwrap();
+        assert_eq!(tag.name, "SetVar");
+        assert_eq!(tag.kind, TagKind::Function);
+        assert_eq!(tag.required_attributes, &["name"]);
+        assert!(tag.accepts_attribute("Value"));
+        assert!(!tag.accepts_attribute("foo"));
+
+        assert_eq!(CATALOG.tag("If").unwrap().kind, TagKind::Conditional);
+        assert_eq!(
+            CATALOG.tag("EntriesHeader").unwrap().kind,
+            TagKind::Conditional
+        );
+        assert!(CATALOG.accepts_attribute("Entries", "lastn"));
+        assert!(!CATALOG.accepts_attribute("Entries", "foo"));
+        assert_eq!(CATALOG.tag("App:Form").unwrap().kind, TagKind::Block);
+        assert_eq!(CATALOG.modifier("encode_html").unwrap().arity, 1);
+        assert!(CATALOG.tag("NoSuchTag").is_none());
+    }
+
+    #[test]
+    fn test_for_version() {
+        let mt6 = TagCatalog::for_version(MtVersion::Mt6, Edition::Standard);
+        assert!(mt6.tag("TypeKeyToken").is_some());
+        assert!(mt6.tag("BlockEditorBlocks").is_none());
+        assert!(mt6.tag("EntryCustomFields").is_none());
+        assert!(mt6.modifier("encode_html").is_some());
+
+        let mt7 = TagCatalog::for_version(MtVersion::Mt7, Edition::CommercialPack);
+        assert!(mt7.tag("TypeKeyToken").is_none());
+        assert!(mt7.tag("ContentFields").is_some());
+        assert!(mt7.tag("EntryCustomFields").is_some());
+
+        let mt8 = TagCatalog::for_version(MtVersion::Mt8, Edition::Standard);
+        assert!(mt8.tag("BlockEditorBlocks").is_some());
+        assert!(mt8.tag("CustomFieldValue").is_none());
+        assert!(TagCatalog::new().tag("TypeKeyToken").is_some());
+    }
+
+    #[test]
+    fn test_accepts_attribute() {
+        assert!(CATALOG.accepts_attribute("SetVar", "name"));
+        assert!(CATALOG.accepts_attribute("SetVar", "encode_html"));
+        assert!(!CATALOG.accepts_attribute("SetVar", "foo"));
+        assert!(CATALOG.accepts_attribute("Include", "foo"));
+        assert!(CATALOG.accepts_attribute("NoSuchTag", "foo"));
+    }
+
+    #[test]
+    fn test_looks_conditional() {
+        // Header and footer tags such as `EntriesHeader` are conditionals by
+        // registration only.
+        for tag in CATALOG.tags() {
+            let header = tag.name.ends_with("Header") || tag.name.ends_with("Footer");
+            if tag.kind == TagKind::Conditional && !header {
+                assert!(looks_conditional(tag.name), "{}", tag.name);
+            }
+        }
+        assert!(looks_conditional("IfMyPlugin"));
+        assert!(looks_conditional("if"));
+        assert!(looks_conditional("UNLESS"));
+        assert!(!looks_conditional("ifmyplugin"));
+        assert!(!looks_conditional("iframe"));
+        assert!(!looks_conditional("hashtags"));
+        assert!(!looks_conditional("issues"));
+        assert!(!looks_conditional("widgetsetexists"));
+        assert!(!looks_conditional("Entries"));
+        assert!(!looks_conditional("Iframe"));
+        assert!(!looks_conditional("EntryIsland"));
+    }
+
+    #[test]
+    fn test_function_tags() {
+        let function_tags = FUNCTION_TAGS.lock().unwrap();
+        assert!(function_tags.contains("entrytitle"));
+        assert!(function_tags.contains("elseif"));
+        assert!(!function_tags.contains("entries"));
+        assert_eq!(
+            function_tags.len(),
+            TAGS.iter()
+                .filter(|tag| tag.kind == TagKind::Function)
+                .count()
+        );
+    }
+}
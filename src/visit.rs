@@ -0,0 +1,361 @@
+//! Visitors over the AST.
+//!
+//! # Examples
+//!
+//! ```
+//! use mtml_parser::{ast::FunctionTagNode, parse, visit::Visit};
+//!
+//! struct CountTags(usize);
+//!
+//! impl Visit for CountTags {
+//!     fn visit_function_tag(&mut self, _node: &FunctionTagNode) {
+//!         self.0 += 1;
+//!     }
+//! }
+//!
+//! let node = parse("<mt:Entries><mt:EntryTitle><mt:EntryBody></mt:Entries>").unwrap();
+//! let mut count = CountTags(0);
+//! count.visit_node(&node);
+//! assert_eq!(count.0, 2);
+//! ```
+
+use super::ast::{Node::*, *};
+
+/// Returned by `enter` to decide whether to visit a node's contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisitControl {
+    Continue,
+    SkipChildren,
+}
+
+/// Visitor over an AST.
+///
+/// Every method has a default implementation that walks into the node's
+/// children, so an implementation only overrides the methods it needs.
+pub trait Visit {
+    /// Called before a node is visited. Return `VisitControl::SkipChildren`
+    /// to skip the node's contents; `leave` is still called.
+    fn enter(&mut self, _node: &Node) -> VisitControl {
+        return VisitControl::Continue;
+    }
+
+    /// Called after a node and its contents have been visited.
+    fn leave(&mut self, _node: &Node) {}
+
+    fn visit_node(&mut self, node: &Node) {
+        walk_node(self, node);
+    }
+
+    fn visit_root(&mut self, node: &RootNode) {
+        walk_root(self, node);
+    }
+
+    fn visit_text(&mut self, _node: &TextNode) {}
+
+    fn visit_function_tag(&mut self, node: &FunctionTagNode) {
+        walk_function_tag(self, node);
+    }
+
+    fn visit_block_tag(&mut self, node: &BlockTagNode) {
+        walk_block_tag(self, node);
+    }
+
+    fn visit_ignore(&mut self, node: &IgnoreNode) {
+        walk_ignore(self, node);
+    }
+
+    fn visit_comment(&mut self, _node: &CommentNode) {}
+
+    fn visit_error(&mut self, _node: &ErrorNode) {}
+
+    fn visit_missing(&mut self, _node: &MissingNode) {}
+
+    fn visit_attribute(&mut self, attribute: &Attribute) {
+        walk_attribute(self, attribute);
+    }
+
+    fn visit_attribute_value(&mut self, value: &AttributeValue) {
+        walk_attribute_value(self, value);
+    }
+}
+
+/// Visit `node` between `enter` and `leave`, unless `enter` skips it.
+pub fn walk_node<V: Visit + ?Sized>(visitor: &mut V, node: &Node) {
+    if visitor.enter(node) == VisitControl::Continue {
+        match node {
+            Root(node) => visitor.visit_root(node),
+            Text(node) => visitor.visit_text(node),
+            FunctionTag(node) => visitor.visit_function_tag(node),
+            BlockTag(node) => visitor.visit_block_tag(node),
+            Ignore(node) => visitor.visit_ignore(node),
+            Comment(node) => visitor.visit_comment(node),
+            Error(node) => visitor.visit_error(node),
+            Missing(node) => visitor.visit_missing(node),
+        }
+    }
+    visitor.leave(node);
+}
+
+pub fn walk_root<V: Visit + ?Sized>(visitor: &mut V, node: &RootNode) {
+    for child in node.children.iter() {
+        visitor.visit_node(child);
+    }
+}
+
+pub fn walk_function_tag<V: Visit + ?Sized>(visitor: &mut V, node: &FunctionTagNode) {
+    for attribute in node.attributes.iter() {
+        visitor.visit_attribute(attribute);
+    }
+}
+
+pub fn walk_block_tag<V: Visit + ?Sized>(visitor: &mut V, node: &BlockTagNode) {
+    for attribute in node.attributes.iter() {
+        visitor.visit_attribute(attribute);
+    }
+    for child in node.children.iter() {
+        visitor.visit_node(child);
+    }
+}
+
+pub fn walk_ignore<V: Visit + ?Sized>(visitor: &mut V, node: &IgnoreNode) {
+    for attribute in node.attributes.iter() {
+        visitor.visit_attribute(attribute);
+    }
+}
+
+pub fn walk_attribute<V: Visit + ?Sized>(visitor: &mut V, attribute: &Attribute) {
+    for value in attribute.values.iter() {
+        visitor.visit_attribute_value(value);
+    }
+}
+
+/// Visit the nodes parsed from an attribute value, if any.
+pub fn walk_attribute_value<V: Visit + ?Sized>(visitor: &mut V, value: &AttributeValue) {
+    for child in value.children.iter().flatten() {
+        visitor.visit_node(child);
+    }
+}
+
+/// Mutable visitor over an AST, which may modify nodes in place.
+///
+/// Every method has a default implementation that walks into the node's
+/// children, so an implementation only overrides the methods it needs.
+pub trait VisitMut {
+    /// Called before a node is visited. Return `VisitControl::SkipChildren`
+    /// to skip the node's contents; `leave` is still called.
+    fn enter(&mut self, _node: &mut Node) -> VisitControl {
+        return VisitControl::Continue;
+    }
+
+    /// Called after a node and its contents have been visited.
+    fn leave(&mut self, _node: &mut Node) {}
+
+    fn visit_node(&mut self, node: &mut Node) {
+        walk_node_mut(self, node);
+    }
+
+    fn visit_root(&mut self, node: &mut RootNode) {
+        walk_root_mut(self, node);
+    }
+
+    fn visit_text(&mut self, _node: &mut TextNode) {}
+
+    fn visit_function_tag(&mut self, node: &mut FunctionTagNode) {
+        walk_function_tag_mut(self, node);
+    }
+
+    fn visit_block_tag(&mut self, node: &mut BlockTagNode) {
+        walk_block_tag_mut(self, node);
+    }
+
+    fn visit_ignore(&mut self, node: &mut IgnoreNode) {
+        walk_ignore_mut(self, node);
+    }
+
+    fn visit_comment(&mut self, _node: &mut CommentNode) {}
+
+    fn visit_error(&mut self, _node: &mut ErrorNode) {}
+
+    fn visit_missing(&mut self, _node: &mut MissingNode) {}
+
+    fn visit_attribute(&mut self, attribute: &mut Attribute) {
+        walk_attribute_mut(self, attribute);
+    }
+
+    fn visit_attribute_value(&mut self, value: &mut AttributeValue) {
+        walk_attribute_value_mut(self, value);
+    }
+}
+
+/// Visit `node` between `enter` and `leave`, unless `enter` skips it.
+pub fn walk_node_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut Node) {
+    if visitor.enter(node) == VisitControl::Continue {
+        match node {
+            Root(node) => visitor.visit_root(node),
+            Text(node) => visitor.visit_text(node),
+            FunctionTag(node) => visitor.visit_function_tag(node),
+            BlockTag(node) => visitor.visit_block_tag(node),
+            Ignore(node) => visitor.visit_ignore(node),
+            Comment(node) => visitor.visit_comment(node),
+            Error(node) => visitor.visit_error(node),
+            Missing(node) => visitor.visit_missing(node),
+        }
+    }
+    visitor.leave(node);
+}
+
+pub fn walk_root_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut RootNode) {
+    for child in node.children.iter_mut() {
+        visitor.visit_node(child);
+    }
+}
+
+pub fn walk_function_tag_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut FunctionTagNode) {
+    for attribute in node.attributes.iter_mut() {
+        visitor.visit_attribute(attribute);
+    }
+}
+
+pub fn walk_block_tag_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut BlockTagNode) {
+    for attribute in node.attributes.iter_mut() {
+        visitor.visit_attribute(attribute);
+    }
+    for child in node.children.iter_mut() {
+        visitor.visit_node(child);
+    }
+}
+
+pub fn walk_ignore_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut IgnoreNode) {
+    for attribute in node.attributes.iter_mut() {
+        visitor.visit_attribute(attribute);
+    }
+}
+
+pub fn walk_attribute_mut<V: VisitMut + ?Sized>(visitor: &mut V, attribute: &mut Attribute) {
+    for value in attribute.values.iter_mut() {
+        visitor.visit_attribute_value(value);
+    }
+}
+
+/// Visit the nodes parsed from an attribute value, if any.
+pub fn walk_attribute_value_mut<V: VisitMut + ?Sized>(visitor: &mut V, value: &mut AttributeValue) {
+    for child in value.children.iter_mut().flatten() {
+        visitor.visit_node(child);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parser::*;
+    use super::*;
+
+    #[derive(Default)]
+    struct Trace {
+        events: Vec<String>,
+        skip: &'static str,
+    }
+
+    fn name(node: &Node) -> String {
+        return match node {
+            Root(_) => "Root".to_string(),
+            Text(node) => format!("{:?}", node.value),
+            FunctionTag(node) => node.name.clone(),
+            BlockTag(node) => node.name.clone(),
+            Ignore(node) => node.name.clone(),
+            Comment(_) => "Comment".to_string(),
+            Error(_) => "Error".to_string(),
+            Missing(_) => "Missing".to_string(),
+        };
+    }
+
+    impl Visit for Trace {
+        fn enter(&mut self, node: &Node) -> VisitControl {
+            self.events.push(format!("enter {}", name(node)));
+            return if name(node) == self.skip {
+                VisitControl::SkipChildren
+            } else {
+                VisitControl::Continue
+            };
+        }
+
+        fn leave(&mut self, node: &Node) {
+            self.events.push(format!("leave {}", name(node)));
+        }
+
+        fn visit_attribute(&mut self, attribute: &Attribute) {
+            self.events.push(format!("attribute {}", attribute.name));
+            walk_attribute(self, attribute);
+        }
+    }
+
+    #[test]
+    fn test_visit() {
+        let node = parse(r#"<mt:Entries lastn="1"><mt:EntryTitle>a</mt:Entries>"#).unwrap();
+        let mut trace = Trace::default();
+        trace.visit_node(&node);
+        assert_eq!(
+            trace.events,
+            vec![
+                "enter Root",
+                "enter Entries",
+                "attribute lastn",
+                "enter EntryTitle",
+                "leave EntryTitle",
+                "enter \"a\"",
+                "leave \"a\"",
+                "leave Entries",
+                "leave Root",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_visit_skip_children() {
+        let node = parse(r#"<mt:Entries lastn="1"><mt:EntryTitle></mt:Entries>"#).unwrap();
+        let mut trace = Trace {
+            skip: "Entries",
+            ..Default::default()
+        };
+        trace.visit_node(&node);
+        assert_eq!(
+            trace.events,
+            vec!["enter Root", "enter Entries", "leave Entries", "leave Root"]
+        );
+    }
+
+    #[test]
+    fn test_visit_attribute_tags() {
+        let node = ParserConfig::default()
+            .attribute_tags(true)
+            .build()
+            .parse(r#"<$mt:Var name="x" value="<$mt:EntryTitle$>"$>"#)
+            .unwrap();
+        let mut trace = Trace::default();
+        trace.visit_node(&node);
+        assert!(trace.events.contains(&"enter EntryTitle".to_string()));
+    }
+
+    struct Rename;
+
+    impl VisitMut for Rename {
+        fn visit_function_tag(&mut self, node: &mut FunctionTagNode) {
+            node.name = node.name.to_uppercase();
+            walk_function_tag_mut(self, node);
+        }
+
+        fn visit_attribute_value(&mut self, value: &mut AttributeValue) {
+            value.value = value.value.to_uppercase();
+        }
+    }
+
+    #[test]
+    fn test_visit_mut() {
+        let mut node =
+            parse(r#"<mt:Entries><mt:EntryTitle remove_html="a"></mt:Entries>"#).unwrap();
+        Rename.visit_node(&mut node);
+        assert_eq!(
+            crate::serializer::serialize(node, None),
+            r#"<mt:Entries><$mt:ENTRYTITLE remove_html="A"$></mt:Entries>"#
+        );
+    }
+}